@@ -1,8 +1,10 @@
-use criterion::{black_box, criterion_group, criterion_main, Criterion, BenchmarkId};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
 use unrolled_linked_list::UnrolledLinkedList;
+use unrolled_linked_list::inline::InlineUnrolledLinkedList;
 use std::collections::LinkedList;
 
 #[derive(Clone)]
+#[allow(dead_code)]
 struct TestStruct {
     id: i32,
     name: String,
@@ -26,6 +28,14 @@ pub fn push_bench(c: &mut Criterion) {
             }
         }
     ));
+    group.bench_function("unrolled_linked_list_inline", |b| b.iter(||
+        {
+            let mut unrolled_list = InlineUnrolledLinkedList::<TestStruct, 8>::new();
+            for el in 1..100 {
+                unrolled_list.push(black_box(TestStruct::new(el)))
+            }
+        }
+    ));
     group.bench_function("vec", |b| b.iter(||
         {
             let mut v = vec![];
@@ -125,6 +135,14 @@ pub fn insert_middle_bench(c: &mut Criterion) {
             }
         }
     ));
+    group.bench_function("unrolled_linked_list_inline", |b| b.iter(||
+        {
+            let mut unrolled_list = InlineUnrolledLinkedList::<TestStruct, 8>::new();
+            for el in 1..100 {
+                unrolled_list.insert(unrolled_list.len() / 2, black_box(TestStruct::new(el)))
+            }
+        }
+    ));
     group.bench_function("vec", |b| b.iter(||
         {
             let mut v = vec![];
@@ -159,6 +177,31 @@ pub fn get_bench(c: &mut Criterion) {
             }
         }
     ));
+    group.bench_function("unrolled_linked_list_indexed", |b| b.iter(||
+        {
+            let mut unrolled_list = UnrolledLinkedList::<TestStruct>::new();
+            unrolled_list.set_indexed(true);
+            for el in 1..100 {
+                unrolled_list.push(black_box(TestStruct::new(el)));
+            }
+
+            for el in 1..100 {
+                let _ = unrolled_list.get(el);
+            }
+        }
+    ));
+    group.bench_function("unrolled_linked_list_inline", |b| b.iter(||
+        {
+            let mut unrolled_list = InlineUnrolledLinkedList::<TestStruct, 8>::new();
+            for el in 1..100 {
+                unrolled_list.push(black_box(TestStruct::new(el)));
+            }
+
+            for el in 1..100 {
+                let _ = unrolled_list.get(el);
+            }
+        }
+    ));
     group.bench_function("vec", |b| b.iter(||
         {
             let mut v = vec![];
@@ -177,7 +220,7 @@ pub fn get_bench(c: &mut Criterion) {
             for el in 1..100 {
                 linked_list.push_back(black_box(TestStruct::new(el)))
             }
-            for el in 1..100 {
+            for _ in 1..100 {
                 let _ = linked_list.pop_back();
             }
         }
@@ -218,7 +261,7 @@ pub fn remove_bench(c: &mut Criterion) {
             for el in 1..100 {
                 linked_list.push_back(black_box(TestStruct::new(el)))
             }
-            for el in (1..30).step_by(2) {
+            for _ in (1..30).step_by(2) {
                 let _ = linked_list.pop_back();
             }
         }
@@ -241,6 +284,18 @@ pub fn iter_bench(c: &mut Criterion) {
             }
         }
     ));
+    group.bench_function("unrolled_linked_list_inline", |b| b.iter(||
+        {
+            let mut unrolled_list = InlineUnrolledLinkedList::<TestStruct, 8>::new();
+            for el in 1..100 {
+                unrolled_list.push(black_box(TestStruct::new(el)));
+            }
+
+            for el in unrolled_list.iter() {
+               let _x = el;
+            }
+        }
+    ));
     group.bench_function("vec", |b| b.iter(||
         {
             let mut v = vec![];
@@ -347,6 +402,33 @@ pub fn into_iter_bench(c: &mut Criterion) {
     group.finish();
 }
 
+pub fn indexed_scaling_bench(c: &mut Criterion) {
+    let mut group = c.benchmark_group("indexed_scaling");
+    group.sample_size(10);
+
+    for len in [10_000u64, 100_000, 1_000_000].iter().copied() {
+        group.bench_with_input(BenchmarkId::new("push", len), &len, |b, &len| b.iter(||
+            {
+                let mut unrolled_list = UnrolledLinkedList::with_capacity(8);
+                unrolled_list.set_indexed(true);
+                for el in 0..len {
+                    unrolled_list.push(black_box(el));
+                }
+            }
+        ));
+        group.bench_with_input(BenchmarkId::new("insert_middle", len), &len, |b, &len| b.iter(||
+            {
+                let mut unrolled_list = UnrolledLinkedList::with_capacity(8);
+                unrolled_list.set_indexed(true);
+                for el in 0..len / 10 {
+                    unrolled_list.insert(unrolled_list.len() / 2, black_box(el));
+                }
+            }
+        ));
+    }
+    group.finish();
+}
+
 criterion_group!(benches,
 push_bench,
 pop_bench,
@@ -357,5 +439,6 @@ remove_bench,
 iter_bench,
 iter_mut_bench,
 into_iter_bench,
+indexed_scaling_bench,
 );
 criterion_main!(benches);
\ No newline at end of file
@@ -0,0 +1,422 @@
+use core::marker::PhantomData;
+use core::mem::{ManuallyDrop, MaybeUninit};
+use core::ops::{Deref, DerefMut};
+use core::ptr;
+use core::slice;
+use allocator_api2::alloc::{Allocator, Global};
+use allocator_api2::collections::TryReserveError;
+use allocator_api2::vec::Vec;
+
+/// The buffer of a node.
+///
+/// The elements are kept in one piece like in a `Vec`, but they can start anywhere in the buffer,
+/// so the room left at the front by the removed first elements is used by the elements added first.
+/// Adding or removing an element at either end is *O*(1) amortized: the elements are moved only
+/// when the end they are added to has no room, and then all the room is given to that end.
+pub(crate) struct NodeBuf<T, A: Allocator = Global> {
+    // every slot is counted as an element of the vector, so it is never shrunk and a reallocation keeps all of them
+    slots: Vec<MaybeUninit<T>, A>,
+    start: usize,
+    len: usize,
+    marker: PhantomData<T>,
+}
+
+impl<T, A: Allocator> NodeBuf<T, A> {
+    pub(crate) fn new_in(alloc: A) -> Self {
+        NodeBuf { slots: Vec::new_in(alloc), start: 0, len: 0, marker: PhantomData }
+    }
+
+    #[inline]
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    pub(crate) fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Returns the pointer to the first element, the elements follow it up to the len.
+    #[inline]
+    pub(crate) fn as_ptr(&self) -> *const T {
+        unsafe { (self.slots.as_ptr() as *const T).add(self.start) }
+    }
+
+    /// Returns the pointer to the first element without borrowing the elements.
+    #[inline]
+    pub(crate) fn as_mut_ptr(&mut self) -> *mut T {
+        unsafe { (self.slots.as_mut_ptr() as *mut T).add(self.start) }
+    }
+
+    /// Sets the number of the elements following the first one.
+    ///
+    /// # Safety
+    /// The elements up to `len` have to be initialized and to fit in the buffer.
+    #[inline]
+    pub(crate) unsafe fn set_len(&mut self, len: usize) {
+        debug_assert!(self.start + len <= self.capacity());
+        self.len = len;
+    }
+
+    #[inline]
+    pub(crate) fn as_slice(&self) -> &[T] {
+        unsafe { slice::from_raw_parts(self.as_ptr(), self.len) }
+    }
+
+    #[inline]
+    pub(crate) fn as_mut_slice(&mut self) -> &mut [T] {
+        unsafe { slice::from_raw_parts_mut(self.as_mut_ptr(), self.len) }
+    }
+
+    /// Grows the buffer to hold at least `additional` more elements, exactly if it has to grow.
+    pub(crate) fn reserve_exact(&mut self, additional: usize) {
+        if let Some(missing) = (self.len + additional).checked_sub(self.capacity()).filter(|&missing| missing > 0) {
+            self.slots.reserve_exact(missing);
+            self.fill_slots();
+        }
+    }
+
+    pub(crate) fn try_reserve_exact(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        if let Some(missing) = (self.len + additional).checked_sub(self.capacity()).filter(|&missing| missing > 0) {
+            self.slots.try_reserve_exact(missing)?;
+            self.fill_slots();
+        }
+        Ok(())
+    }
+
+    /// Grows the buffer to hold at least `additional` more elements, at least doubling it if it has to grow.
+    pub(crate) fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        if let Some(missing) = (self.len + additional).checked_sub(self.capacity()).filter(|&missing| missing > 0) {
+            self.slots.try_reserve(missing)?;
+            self.fill_slots();
+        }
+        Ok(())
+    }
+
+    /// Counts the whole allocation as the slots.
+    #[inline]
+    fn fill_slots(&mut self) {
+        // the slots are `MaybeUninit`, so all of them are valid
+        unsafe { self.slots.set_len(self.slots.capacity()) }
+    }
+
+    /// Moves the elements to start at the given slot.
+    #[inline]
+    fn move_to(&mut self, start: usize) {
+        if start != self.start {
+            unsafe {
+                let base = self.slots.as_mut_ptr() as *mut T;
+                ptr::copy(base.add(self.start), base.add(start), self.len);
+            }
+            self.start = start;
+        }
+    }
+
+    /// Makes room for `additional` elements after the last one.
+    #[inline]
+    fn room_back(&mut self, additional: usize) {
+        if self.start + self.len + additional > self.capacity() {
+            if self.len + additional > self.capacity() {
+                self.slots.reserve(self.len + additional - self.capacity());
+                self.fill_slots();
+            }
+            self.move_to(0);
+        }
+    }
+
+    /// Makes room for an element before the first one.
+    #[inline]
+    fn room_front(&mut self) {
+        if self.start == 0 {
+            if self.len == self.capacity() {
+                self.slots.reserve(1);
+                self.fill_slots();
+            }
+            self.move_to(self.capacity() - self.len);
+        }
+    }
+
+    pub(crate) fn push(&mut self, el: T) {
+        self.room_back(1);
+        unsafe { self.as_mut_ptr().add(self.len).write(el) }
+        self.len += 1;
+    }
+
+    pub(crate) fn push_front(&mut self, el: T) {
+        self.room_front();
+        self.start -= 1;
+        unsafe { self.as_mut_ptr().write(el) }
+        self.len += 1;
+    }
+
+    pub(crate) fn pop(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        unsafe { Some(self.as_ptr().add(self.len).read()) }
+    }
+
+    pub(crate) fn pop_front(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+        let el = unsafe { self.as_ptr().read() };
+        self.start += 1;
+        self.len -= 1;
+        Some(el)
+    }
+
+    /// Inserts the element moving the elements on the shorter side of it if there is room on that side.
+    ///
+    /// # Panics
+    /// Panics if `idx > len`.
+    pub(crate) fn insert(&mut self, idx: usize, el: T) {
+        assert!(idx <= self.len, "insertion index {} should be less or equal the len {}", idx, self.len);
+        let back_full = self.start + self.len == self.capacity();
+        unsafe {
+            if self.start > 0 && (idx < self.len / 2 || back_full) {
+                let ptr = self.as_mut_ptr();
+                ptr::copy(ptr, ptr.sub(1), idx);
+                self.start -= 1;
+            } else {
+                self.room_back(1);
+                let ptr = self.as_mut_ptr();
+                ptr::copy(ptr.add(idx), ptr.add(idx + 1), self.len - idx);
+            }
+            self.as_mut_ptr().add(idx).write(el);
+        }
+        self.len += 1;
+    }
+
+    /// Removes the element moving the elements on the shorter side of it.
+    ///
+    /// # Panics
+    /// Panics if `idx >= len`.
+    pub(crate) fn remove(&mut self, idx: usize) -> T {
+        assert!(idx < self.len, "removal index {} should be less than the len {}", idx, self.len);
+        unsafe {
+            let ptr = self.as_mut_ptr();
+            let el = ptr.add(idx).read();
+            if idx < self.len / 2 {
+                ptr::copy(ptr, ptr.add(1), idx);
+                self.start += 1;
+            } else {
+                ptr::copy(ptr.add(idx + 1), ptr.add(idx), self.len - idx - 1);
+            }
+            self.len -= 1;
+            el
+        }
+    }
+
+    /// Drops all elements keeping the buffer.
+    pub(crate) fn clear(&mut self) {
+        let len = self.len;
+        // the elements are forgotten first, so a panic in a destructor does not drop them twice
+        self.len = 0;
+        unsafe { ptr::drop_in_place(ptr::slice_from_raw_parts_mut(self.as_mut_ptr(), len)) }
+        self.start = 0;
+    }
+
+    /// Moves all elements of `other` after the last element.
+    pub(crate) fn append(&mut self, other: &mut Self) {
+        let len = other.len;
+        self.append_front_of(other, len);
+    }
+
+    /// Moves the first `count` elements of `other` after the last element.
+    pub(crate) fn append_front_of(&mut self, other: &mut Self, count: usize) {
+        assert!(count <= other.len);
+        self.room_back(count);
+        unsafe { ptr::copy_nonoverlapping(other.as_ptr(), self.as_mut_ptr().add(self.len), count) }
+        other.start += count;
+        other.len -= count;
+        self.len += count;
+    }
+
+    /// Moves the elements of `other` starting from `at` after the last element.
+    pub(crate) fn append_tail_of(&mut self, other: &mut Self, at: usize) {
+        assert!(at <= other.len);
+        let count = other.len - at;
+        self.room_back(count);
+        unsafe { ptr::copy_nonoverlapping(other.as_ptr().add(at), self.as_mut_ptr().add(self.len), count) }
+        other.len = at;
+        self.len += count;
+    }
+
+    /// Turns the buffer into a vector, moving the elements to the start of it.
+    pub(crate) fn into_vec(mut self) -> Vec<T, A> {
+        self.move_to(0);
+        let buf = ManuallyDrop::new(self);
+        unsafe {
+            let (ptr, _, cap, alloc) = ptr::read(&buf.slots).into_raw_parts_with_alloc();
+            Vec::from_raw_parts_in(ptr as *mut T, buf.len, cap, alloc)
+        }
+    }
+
+    pub(crate) fn clone_from(&mut self, source: &Self) where T: Clone {
+        self.clear();
+        self.extend(source.iter().cloned());
+    }
+}
+
+impl<T, A: Allocator> Extend<T> for NodeBuf<T, A> {
+    fn extend<I: IntoIterator<Item=T>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.room_back(iter.size_hint().0);
+        iter.for_each(|el| self.push(el));
+    }
+}
+
+impl<T, A: Allocator> Deref for NodeBuf<T, A> {
+    type Target = [T];
+
+    #[inline]
+    fn deref(&self) -> &[T] {
+        self.as_slice()
+    }
+}
+
+impl<T, A: Allocator> DerefMut for NodeBuf<T, A> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut [T] {
+        self.as_mut_slice()
+    }
+}
+
+impl<T, A: Allocator> Drop for NodeBuf<T, A> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::rc::Rc;
+    use std::vec;
+    use std::vec::Vec;
+    use allocator_api2::alloc::Global;
+    use crate::tests::DropCounter;
+    use super::NodeBuf;
+
+    fn buf_of(els: &[i32], cap: usize) -> NodeBuf<i32> {
+        let mut buf = NodeBuf::new_in(Global);
+        buf.reserve_exact(cap);
+        buf.extend(els.iter().copied());
+        buf
+    }
+
+    #[test]
+    fn front_test() {
+        let mut buf = buf_of(&[], 8);
+        for el in 0..8 {
+            buf.push_front(el);
+        }
+        assert_eq!(buf.capacity(), 8);
+        assert_eq!(buf.to_vec(), vec![7, 6, 5, 4, 3, 2, 1, 0]);
+        assert_eq!(buf.pop_front(), Some(7));
+        assert_eq!(buf.pop(), Some(0));
+        assert_eq!(buf.start, 1);
+        buf.push_front(8);
+        assert_eq!(buf.start, 0);
+        // the back has room, so all of it is given to the front
+        buf.push_front(9);
+        assert_eq!(buf.start, 0);
+        assert_eq!(buf.to_vec(), vec![9, 8, 6, 5, 4, 3, 2, 1]);
+        buf.push_front(10);
+        assert!(buf.capacity() > 8);
+        assert_eq!(buf.to_vec(), vec![10, 9, 8, 6, 5, 4, 3, 2, 1]);
+        while buf.pop_front().is_some() {}
+        assert!(buf.is_empty());
+        assert_eq!(buf.pop(), None);
+    }
+
+    #[test]
+    fn moves_test() {
+        // every element is moved a few times on average while the buffer is used as a queue
+        let mut buf = buf_of(&[0, 1, 2, 3, 4], 8);
+        let mut moved = 0;
+        for el in 5..1000 {
+            let start = buf.start;
+            buf.push(el);
+            if buf.start != start {
+                moved += buf.len();
+            }
+            assert_eq!(buf.pop_front(), Some(el - 5));
+        }
+        assert!(moved <= 2 * 1000, "{} moves", moved);
+        assert_eq!(buf.capacity(), 8);
+    }
+
+    #[test]
+    fn insert_remove_test() {
+        for cap in 0..12 {
+            for start in 0..=cap.min(6) {
+                for idx in 0..=6 {
+                    let mut buf = buf_of(&[0, 1, 2, 3, 4, 5, 6, 7], cap);
+                    for _ in 0..start {
+                        buf.pop_front();
+                    }
+                    let mut expected = buf.to_vec();
+                    if idx <= expected.len() {
+                        buf.insert(idx, 100);
+                        expected.insert(idx, 100);
+                        assert_eq!(buf.to_vec(), expected);
+                    }
+                    if idx < expected.len() {
+                        assert_eq!(buf.remove(idx), expected.remove(idx));
+                        assert_eq!(buf.to_vec(), expected);
+                    }
+                    assert_eq!(buf.into_vec().as_slice(), expected.as_slice());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn append_test() {
+        let mut buf = buf_of(&[0, 1, 2, 3], 6);
+        buf.pop_front();
+        let mut other = buf_of(&[4, 5, 6, 7, 8], 6);
+        buf.append_front_of(&mut other, 2);
+        assert_eq!(buf.to_vec(), vec![1, 2, 3, 4, 5]);
+        assert_eq!(other.to_vec(), vec![6, 7, 8]);
+        buf.append_tail_of(&mut other, 1);
+        assert_eq!(buf.to_vec(), vec![1, 2, 3, 4, 5, 7, 8]);
+        assert_eq!(other.to_vec(), vec![6]);
+        buf.append(&mut other);
+        assert_eq!(buf.to_vec(), vec![1, 2, 3, 4, 5, 7, 8, 6]);
+        assert!(other.is_empty());
+        let mut copy = buf_of(&[9], 1);
+        copy.clone_from(&buf);
+        assert_eq!(copy.to_vec(), buf.to_vec());
+    }
+
+    #[test]
+    fn drop_test() {
+        let dropped = Cell::new(0);
+        let mut buf = NodeBuf::new_in(Global);
+        for _ in 0..5 {
+            buf.push_front(DropCounter(&dropped));
+        }
+        drop(buf.pop_front());
+        drop(buf.remove(1));
+        assert_eq!(dropped.get(), 2);
+        let vec: Vec<_> = buf.into_vec().into_iter().collect();
+        assert_eq!(vec.len(), 3);
+        assert_eq!(dropped.get(), 2);
+        drop(vec);
+        assert_eq!(dropped.get(), 5);
+
+        let item = Rc::new(0);
+        let mut buf = NodeBuf::new_in(Global);
+        buf.extend((0..6).map(|_| item.clone()));
+        buf.pop_front();
+        buf.clear();
+        assert_eq!(Rc::strong_count(&item), 1);
+        buf.push(item.clone());
+        drop(buf);
+        assert_eq!(Rc::strong_count(&item), 1);
+    }
+}
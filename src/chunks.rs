@@ -0,0 +1,383 @@
+//! The access to the node buffers as slices.
+//!
+//! Every node keeps its elements contiguously, so the slice algorithms, `copy_from_slice`
+//! or SIMD code can run over the nodes one buffer at a time instead of going element by element.
+//!
+//! # Example
+//! ```rust
+//! use unrolled_linked_list::UnrolledLinkedList;
+//!
+//! let mut list = UnrolledLinkedList::with_capacity(4);
+//! list.extend(0..10);
+//! for chunk in list.chunks_mut() {
+//!     chunk.reverse();
+//! }
+//! let sum: i32 = list.chunks().map(|chunk| chunk.iter().sum::<i32>()).sum();
+//! assert_eq!(sum, 45);
+//! ```
+
+use core::ptr::NonNull;
+use core::marker::PhantomData;
+use core::iter::FusedIterator;
+use core::fmt;
+use alloc::vec::Vec;
+use crate::{UnrolledLinkedList, Node};
+use allocator_api2::alloc::{Allocator, Global};
+
+impl<T, A: Allocator + Clone> UnrolledLinkedList<T, A> {
+    /// Provides a double-ended iterator over the buffers of the nodes.
+    ///
+    /// Every yielded slice is non-empty and the slices follow the order of the elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unrolled_linked_list::UnrolledLinkedList;
+    ///
+    /// let list = UnrolledLinkedList::from_iter_with(4, 1.0, 0..10);
+    /// let chunks: Vec<&[i32]> = list.chunks().collect();
+    /// assert_eq!(chunks, vec![&[0, 1, 2, 3][..], &[4, 5, 6, 7], &[8, 9]]);
+    /// ```
+    pub fn chunks(&self) -> Chunks<'_, T, A> {
+        Chunks { span: Span::of(self), marker: PhantomData }
+    }
+
+    /// Provides a double-ended iterator over the mutable buffers of the nodes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unrolled_linked_list::UnrolledLinkedList;
+    ///
+    /// let mut list = UnrolledLinkedList::from_iter_with(4, 1.0, 0..10);
+    /// if let Some(chunk) = list.chunks_mut().next_back() {
+    ///     chunk.copy_from_slice(&[18, 19]);
+    /// }
+    /// assert_eq!(list.iter().copied().collect::<Vec<_>>(), vec![0, 1, 2, 3, 4, 5, 6, 7, 18, 19]);
+    /// ```
+    pub fn chunks_mut(&mut self) -> ChunksMut<'_, T, A> {
+        ChunksMut { span: Span::of(self), marker: PhantomData }
+    }
+
+    /// Provides a double-ended iterator over the positions of the nodes in the list.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unrolled_linked_list::UnrolledLinkedList;
+    /// use unrolled_linked_list::chunks::NodeInfo;
+    ///
+    /// let list = UnrolledLinkedList::from_iter_with(4, 1.0, 0..10);
+    /// assert_eq!(list.nodes().last(), Some(NodeInfo { start: 8, len: 2 }));
+    /// ```
+    pub fn nodes(&self) -> Nodes<'_, T, A> {
+        Nodes { span: Span::of(self), start: 0, end: self.len, marker: PhantomData }
+    }
+
+    /// Returns the buffers of all nodes in order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unrolled_linked_list::UnrolledLinkedList;
+    ///
+    /// let list = UnrolledLinkedList::from_iter_with(3, 1.0, 0..5);
+    /// assert_eq!(list.as_slices(), vec![&[0, 1, 2][..], &[3, 4]]);
+    /// ```
+    pub fn as_slices(&self) -> Vec<&[T]> {
+        self.chunks().collect()
+    }
+}
+
+/// The chain of nodes from `head` to `tail` left to visit and the number of elements in them.
+struct Span<T, A: Allocator = Global> {
+    head: Option<NonNull<Node<T, A>>>,
+    tail: Option<NonNull<Node<T, A>>>,
+    len: usize,
+}
+
+impl<T, A: Allocator> Clone for Span<T, A> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T, A: Allocator> Copy for Span<T, A> {}
+
+impl<T, A: Allocator> Span<T, A> {
+    fn of(list: &UnrolledLinkedList<T, A>) -> Self where A: Clone {
+        Span { head: list.head, tail: list.tail, len: list.len }
+    }
+
+    #[inline]
+    unsafe fn next(&mut self) -> Option<NonNull<Node<T, A>>> {
+        let node = self.head?;
+        if self.head == self.tail {
+            self.head = None;
+            self.tail = None;
+        } else {
+            self.head = node.as_ref().next;
+        }
+        self.len -= node.as_ref().data.len();
+        Some(node)
+    }
+
+    #[inline]
+    unsafe fn next_back(&mut self) -> Option<NonNull<Node<T, A>>> {
+        let node = self.tail?;
+        if self.head == self.tail {
+            self.head = None;
+            self.tail = None;
+        } else {
+            self.tail = node.as_ref().prev;
+        }
+        self.len -= node.as_ref().data.len();
+        Some(node)
+    }
+
+    /// Every node left holds at least one element.
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len.min(1), Some(self.len))
+    }
+}
+
+/// An iterator over the buffers of the nodes of a `UnrolledLinkedList`.
+///
+/// This `struct` is created by [`UnrolledLinkedList::chunks()`]. See its documentation for more.
+pub struct Chunks<'a, T, A: Allocator = Global> {
+    span: Span<T, A>,
+    marker: PhantomData<&'a Node<T, A>>,
+}
+
+unsafe impl<T: Sync, A: Allocator + Sync> Send for Chunks<'_, T, A> {}
+
+unsafe impl<T: Sync, A: Allocator + Sync> Sync for Chunks<'_, T, A> {}
+
+impl<T, A: Allocator> Clone for Chunks<'_, T, A> {
+    fn clone(&self) -> Self {
+        Chunks { ..*self }
+    }
+}
+
+impl<T: fmt::Debug, A: Allocator> fmt::Debug for Chunks<'_, T, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+impl<'a, T, A: Allocator> Iterator for Chunks<'a, T, A> {
+    type Item = &'a [T];
+
+    fn next(&mut self) -> Option<&'a [T]> {
+        unsafe { self.span.next().map(|node| (*node.as_ptr()).data.as_slice()) }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.span.size_hint()
+    }
+
+    #[inline]
+    fn last(mut self) -> Option<&'a [T]> {
+        self.next_back()
+    }
+}
+
+impl<'a, T, A: Allocator> DoubleEndedIterator for Chunks<'a, T, A> {
+    fn next_back(&mut self) -> Option<&'a [T]> {
+        unsafe { self.span.next_back().map(|node| (*node.as_ptr()).data.as_slice()) }
+    }
+}
+
+impl<T, A: Allocator> FusedIterator for Chunks<'_, T, A> {}
+
+/// An iterator over the mutable buffers of the nodes of a `UnrolledLinkedList`.
+///
+/// This `struct` is created by [`UnrolledLinkedList::chunks_mut()`]. See its documentation for more.
+pub struct ChunksMut<'a, T, A: Allocator = Global> {
+    span: Span<T, A>,
+    marker: PhantomData<&'a mut Node<T, A>>,
+}
+
+unsafe impl<T: Send, A: Allocator + Send> Send for ChunksMut<'_, T, A> {}
+
+unsafe impl<T: Sync, A: Allocator + Sync> Sync for ChunksMut<'_, T, A> {}
+
+impl<T, A: Allocator> fmt::Debug for ChunksMut<'_, T, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ChunksMut").field(&self.span.len).finish()
+    }
+}
+
+impl<'a, T, A: Allocator> Iterator for ChunksMut<'a, T, A> {
+    type Item = &'a mut [T];
+
+    fn next(&mut self) -> Option<&'a mut [T]> {
+        unsafe { self.span.next().map(|node| (*node.as_ptr()).data.as_mut_slice()) }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.span.size_hint()
+    }
+
+    #[inline]
+    fn last(mut self) -> Option<&'a mut [T]> {
+        self.next_back()
+    }
+}
+
+impl<'a, T, A: Allocator> DoubleEndedIterator for ChunksMut<'a, T, A> {
+    fn next_back(&mut self) -> Option<&'a mut [T]> {
+        unsafe { self.span.next_back().map(|node| (*node.as_ptr()).data.as_mut_slice()) }
+    }
+}
+
+impl<T, A: Allocator> FusedIterator for ChunksMut<'_, T, A> {}
+
+/// The position of a node in the list: the index of its first element and the number of its elements.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NodeInfo {
+    /// The index of the first element of the node in the list.
+    pub start: usize,
+    /// The number of elements in the node, never zero.
+    pub len: usize,
+}
+
+/// An iterator over the positions of the nodes of a `UnrolledLinkedList`.
+///
+/// This `struct` is created by [`UnrolledLinkedList::nodes()`]. See its documentation for more.
+pub struct Nodes<'a, T, A: Allocator = Global> {
+    span: Span<T, A>,
+    // the index of the first element of the front node and after the last element of the back node
+    start: usize,
+    end: usize,
+    marker: PhantomData<&'a Node<T, A>>,
+}
+
+unsafe impl<T: Sync, A: Allocator + Sync> Send for Nodes<'_, T, A> {}
+
+unsafe impl<T: Sync, A: Allocator + Sync> Sync for Nodes<'_, T, A> {}
+
+impl<T, A: Allocator> Clone for Nodes<'_, T, A> {
+    fn clone(&self) -> Self {
+        Nodes { ..*self }
+    }
+}
+
+impl<T, A: Allocator> fmt::Debug for Nodes<'_, T, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+impl<'a, T, A: Allocator> Iterator for Nodes<'a, T, A> {
+    type Item = NodeInfo;
+
+    fn next(&mut self) -> Option<NodeInfo> {
+        let len = unsafe { self.span.next()?.as_ref().data.len() };
+        let info = NodeInfo { start: self.start, len };
+        self.start += len;
+        Some(info)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.span.size_hint()
+    }
+
+    #[inline]
+    fn last(mut self) -> Option<NodeInfo> {
+        self.next_back()
+    }
+}
+
+impl<'a, T, A: Allocator> DoubleEndedIterator for Nodes<'a, T, A> {
+    fn next_back(&mut self) -> Option<NodeInfo> {
+        let len = unsafe { self.span.next_back()?.as_ref().data.len() };
+        self.end -= len;
+        Some(NodeInfo { start: self.end, len })
+    }
+}
+
+impl<T, A: Allocator> FusedIterator for Nodes<'_, T, A> {}
+
+#[cfg(test)]
+mod tests {
+    use std::vec;
+    use std::vec::Vec;
+    use crate::UnrolledLinkedList;
+    use crate::chunks::NodeInfo;
+    use crate::tests::{check_invariants, node_lens};
+
+    #[test]
+    fn chunks_test() {
+        let mut list = UnrolledLinkedList::with_capacity(4);
+        assert_eq!(list.chunks().next(), None);
+        assert_eq!(list.chunks_mut().next_back(), None);
+        assert_eq!(list.nodes().next(), None);
+
+        for el in 0..20 {
+            list.insert(el / 2, el);
+        }
+        let lens = node_lens(&list);
+        let chunks: Vec<&[usize]> = list.chunks().collect();
+        assert_eq!(chunks.iter().map(|c| c.len()).collect::<Vec<_>>(), lens);
+        assert_eq!(chunks.concat(), list.iter().copied().collect::<Vec<_>>());
+        assert_eq!(list.as_slices(), chunks);
+
+        let mut rev: Vec<&[usize]> = list.chunks().rev().collect();
+        rev.reverse();
+        assert_eq!(rev, chunks);
+
+        let mut iter = list.chunks();
+        let (front, back) = (iter.next().unwrap(), iter.next_back().unwrap());
+        assert_eq!(front, chunks[0]);
+        assert_eq!(back, chunks[chunks.len() - 1]);
+        assert_eq!(iter.count(), chunks.len() - 2);
+    }
+
+    #[test]
+    fn chunks_mut_test() {
+        let mut list = UnrolledLinkedList::from_iter_with(3, 1.0, 0..10);
+        let mut iter = list.chunks_mut();
+        let front = iter.next().unwrap();
+        let back = iter.next_back().unwrap();
+        front.fill(0);
+        back.fill(9);
+        for chunk in iter {
+            chunk.reverse();
+        }
+        check_invariants(&list);
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), vec![0, 0, 0, 5, 4, 3, 8, 7, 6, 9]);
+    }
+
+    #[test]
+    fn nodes_test() {
+        let list = UnrolledLinkedList::from_iter_with(4, 0.75, 0..10);
+        let nodes: Vec<NodeInfo> = list.nodes().collect();
+        assert_eq!(nodes, vec![
+            NodeInfo { start: 0, len: 3 },
+            NodeInfo { start: 3, len: 3 },
+            NodeInfo { start: 6, len: 3 },
+            NodeInfo { start: 9, len: 1 },
+        ]);
+        let mut rev: Vec<NodeInfo> = list.nodes().rev().collect();
+        rev.reverse();
+        assert_eq!(rev, nodes);
+
+        let mut iter = list.nodes();
+        assert_eq!(iter.next_back(), Some(NodeInfo { start: 9, len: 1 }));
+        assert_eq!(iter.next(), Some(NodeInfo { start: 0, len: 3 }));
+        assert_eq!(iter.next_back(), Some(NodeInfo { start: 6, len: 3 }));
+        assert_eq!(iter.next(), Some(NodeInfo { start: 3, len: 3 }));
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.next_back(), None);
+
+        for (info, chunk) in list.nodes().zip(list.chunks()) {
+            assert_eq!(list.get(info.start), chunk.first());
+            assert_eq!(info.len, chunk.len());
+        }
+    }
+}
@@ -0,0 +1,822 @@
+use core::ptr::NonNull;
+use core::mem;
+use core::fmt;
+use crate::{UnrolledLinkedList, Node};
+use crate::iters::{elem, elem_mut};
+use allocator_api2::alloc::{Allocator, Global};
+
+impl<T, A: Allocator + Clone> UnrolledLinkedList<T, A> {
+    /// Provides a cursor at the front element.
+    ///
+    /// The cursor is pointing to the "ghost" non-element if the list is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unrolled_linked_list::UnrolledLinkedList;
+    ///
+    /// let mut list = UnrolledLinkedList::new();
+    /// list.push(1);
+    /// list.push(2);
+    ///
+    /// let mut cursor = list.cursor_front();
+    /// assert_eq!(cursor.current(), Some(&1));
+    /// cursor.move_next();
+    /// assert_eq!(cursor.current(), Some(&2));
+    /// ```
+    pub fn cursor_front(&self) -> Cursor<'_, T, A> {
+        Cursor { pos: Position::front(self), list: self }
+    }
+
+    /// Provides a cursor with editing operations at the front element.
+    ///
+    /// The cursor is pointing to the "ghost" non-element if the list is empty.
+    pub fn cursor_front_mut(&mut self) -> CursorMut<'_, T, A> {
+        CursorMut { pos: Position::front(self), list: self }
+    }
+
+    /// Provides a cursor at the back element.
+    ///
+    /// The cursor is pointing to the "ghost" non-element if the list is empty.
+    pub fn cursor_back(&self) -> Cursor<'_, T, A> {
+        Cursor { pos: Position::back(self), list: self }
+    }
+
+    /// Provides a cursor with editing operations at the back element.
+    ///
+    /// The cursor is pointing to the "ghost" non-element if the list is empty.
+    pub fn cursor_back_mut(&mut self) -> CursorMut<'_, T, A> {
+        CursorMut { pos: Position::back(self), list: self }
+    }
+
+    /// Provides a cursor at the element with the given index.
+    /// The cursor is pointing to the "ghost" non-element if `index == len`.
+    /// # Panics
+    /// Panics if `index > len`.
+    ///
+    /// This operation should compute in *O*(*n*/*cap*) time.
+    pub fn cursor_at(&self, index: usize) -> Cursor<'_, T, A> {
+        Cursor { pos: Position::at(self, index), list: self }
+    }
+
+    /// Provides a cursor with editing operations at the element with the given index.
+    /// The cursor is pointing to the "ghost" non-element if `index == len`.
+    /// # Panics
+    /// Panics if `index > len`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unrolled_linked_list::UnrolledLinkedList;
+    ///
+    /// let mut list = UnrolledLinkedList::with_capacity(4);
+    /// for el in 0..10 {
+    ///     list.push(el);
+    /// }
+    ///
+    /// let mut cursor = list.cursor_at_mut(5);
+    /// cursor.insert_before(10);
+    /// cursor.insert_after(11);
+    /// assert_eq!(cursor.remove_current(), Some(5));
+    /// assert_eq!(cursor.current(), Some(&mut 11));
+    ///
+    /// let vec: Vec<_> = list.into_iter().collect();
+    /// assert_eq!(vec, vec![0, 1, 2, 3, 4, 10, 11, 6, 7, 8, 9]);
+    /// ```
+    pub fn cursor_at_mut(&mut self, index: usize) -> CursorMut<'_, T, A> {
+        CursorMut { pos: Position::at(self, index), list: self }
+    }
+}
+
+/// The position of a cursor: the node, the offset inside the node and the index in the list.
+/// The node is `None` when the cursor is pointing to the "ghost" non-element.
+struct Position<T, A: Allocator = Global> {
+    index: usize,
+    node: Option<NonNull<Node<T, A>>>,
+    offset: usize,
+}
+
+impl<T, A: Allocator> Clone for Position<T, A> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T, A: Allocator> Copy for Position<T, A> {}
+
+impl<T, A: Allocator + Clone> Position<T, A> {
+    fn front(list: &UnrolledLinkedList<T, A>) -> Self {
+        Position { index: 0, node: list.head, offset: 0 }
+    }
+
+    fn back(list: &UnrolledLinkedList<T, A>) -> Self {
+        match Position::prev_of(list, None) {
+            Some((node, offset)) => Position { index: list.len - 1, node: Some(node), offset },
+            None => Position { index: 0, node: None, offset: 0 },
+        }
+    }
+
+    fn at(list: &UnrolledLinkedList<T, A>, index: usize) -> Self {
+        if index > list.len {
+            panic!("index {} should be less or equal the len {}", index, list.len)
+        }
+        match list.find_node(index) {
+            (Some(node), start_idx) => Position { index, node: Some(node), offset: index - start_idx },
+            (None, _) => Position { index, node: None, offset: 0 },
+        }
+    }
+
+    fn next_of(list: &UnrolledLinkedList<T, A>, pos: Option<(NonNull<Node<T, A>>, usize)>) -> Option<(NonNull<Node<T, A>>, usize)> {
+        match pos {
+            None => list.head.map(|node| (node, 0)),
+            Some((node, offset)) => unsafe {
+                if offset + 1 < node.as_ref().data.len() {
+                    Some((node, offset + 1))
+                } else {
+                    node.as_ref().next.map(|next| (next, 0))
+                }
+            }
+        }
+    }
+
+    fn prev_of(list: &UnrolledLinkedList<T, A>, pos: Option<(NonNull<Node<T, A>>, usize)>) -> Option<(NonNull<Node<T, A>>, usize)> {
+        unsafe {
+            match pos {
+                Some((node, offset)) if offset > 0 => Some((node, offset - 1)),
+                Some((node, _)) => node.as_ref().prev.map(|prev| (prev, prev.as_ref().data.len() - 1)),
+                None => list.tail.map(|tail| (tail, tail.as_ref().data.len() - 1)),
+            }
+        }
+    }
+
+    fn current(&self) -> Option<(NonNull<Node<T, A>>, usize)> {
+        self.node.map(|node| (node, self.offset))
+    }
+
+    fn move_next(&mut self, list: &UnrolledLinkedList<T, A>) {
+        match Position::next_of(list, self.current()) {
+            Some((node, offset)) => {
+                self.index = if self.node.is_some() { self.index + 1 } else { 0 };
+                self.node = Some(node);
+                self.offset = offset;
+            }
+            None => self.set_ghost(list),
+        }
+    }
+
+    fn move_prev(&mut self, list: &UnrolledLinkedList<T, A>) {
+        match Position::prev_of(list, self.current()) {
+            Some((node, offset)) => {
+                self.index = if self.node.is_some() { self.index - 1 } else { list.len - 1 };
+                self.node = Some(node);
+                self.offset = offset;
+            }
+            None => self.set_ghost(list),
+        }
+    }
+
+    fn set_ghost(&mut self, list: &UnrolledLinkedList<T, A>) {
+        self.index = list.len;
+        self.node = None;
+        self.offset = 0;
+    }
+
+    /// Moves the position to the next node if the offset has run over the current node.
+    unsafe fn normalize(&mut self) {
+        if let Some(node) = self.node {
+            let len = node.as_ref().data.len();
+            if self.offset >= len {
+                self.node = node.as_ref().next;
+                self.offset -= len;
+            }
+        }
+    }
+}
+
+/// A cursor over a `UnrolledLinkedList`.
+///
+/// A `Cursor` is like an iterator, except that it can freely seek back-and-forth.
+/// Cursors always rest between two elements in the list, and index in a logically circular way.
+/// To accommodate this, there is a "ghost" non-element that yields `None` between the tail and
+/// the head of the list.
+///
+/// This `struct` is created by [`UnrolledLinkedList::cursor_front()`], [`UnrolledLinkedList::cursor_back()`]
+/// and [`UnrolledLinkedList::cursor_at()`].
+pub struct Cursor<'a, T, A: Allocator + Clone = Global> {
+    pos: Position<T, A>,
+    list: &'a UnrolledLinkedList<T, A>,
+}
+
+unsafe impl<T: Sync, A: Allocator + Clone + Sync> Send for Cursor<'_, T, A> {}
+
+unsafe impl<T: Sync, A: Allocator + Clone + Sync> Sync for Cursor<'_, T, A> {}
+
+impl<T, A: Allocator + Clone> Clone for Cursor<'_, T, A> {
+    fn clone(&self) -> Self {
+        Cursor { ..*self }
+    }
+}
+
+impl<T: fmt::Debug, A: Allocator + Clone> fmt::Debug for Cursor<'_, T, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Cursor").field(&self.list).field(&self.index()).finish()
+    }
+}
+
+impl<'a, T, A: Allocator + Clone> Cursor<'a, T, A> {
+    /// Returns the index of the cursor or `None` if the cursor is pointing to the "ghost" non-element.
+    pub fn index(&self) -> Option<usize> {
+        self.pos.node.map(|_| self.pos.index)
+    }
+
+    /// Moves the cursor to the next element.
+    /// The cursor moves to the "ghost" non-element after the last element
+    /// and to the first element after the "ghost" non-element.
+    pub fn move_next(&mut self) {
+        self.pos.move_next(self.list)
+    }
+
+    /// Moves the cursor to the previous element.
+    /// The cursor moves to the "ghost" non-element before the first element
+    /// and to the last element before the "ghost" non-element.
+    pub fn move_prev(&mut self) {
+        self.pos.move_prev(self.list)
+    }
+
+    /// Returns a reference to the element that the cursor is currently pointing to.
+    pub fn current(&self) -> Option<&'a T> {
+        unsafe { self.pos.current().map(|p| elem(p)) }
+    }
+
+    /// Returns a reference to the next element.
+    /// If the cursor is pointing to the "ghost" non-element then this returns the first element.
+    pub fn peek_next(&self) -> Option<&'a T> {
+        unsafe { Position::next_of(self.list, self.pos.current()).map(|p| elem(p)) }
+    }
+
+    /// Returns a reference to the previous element.
+    /// If the cursor is pointing to the "ghost" non-element then this returns the last element.
+    pub fn peek_prev(&self) -> Option<&'a T> {
+        unsafe { Position::prev_of(self.list, self.pos.current()).map(|p| elem(p)) }
+    }
+}
+
+/// A cursor over a `UnrolledLinkedList` with editing operations.
+///
+/// A `CursorMut` works directly on the node it is pointing to, so the editing operations
+/// split or refill only that node and its neighbours instead of searching the list from the head.
+///
+/// This `struct` is created by [`UnrolledLinkedList::cursor_front_mut()`], [`UnrolledLinkedList::cursor_back_mut()`]
+/// and [`UnrolledLinkedList::cursor_at_mut()`].
+pub struct CursorMut<'a, T, A: Allocator + Clone = Global> {
+    pos: Position<T, A>,
+    list: &'a mut UnrolledLinkedList<T, A>,
+}
+
+unsafe impl<T: Send, A: Allocator + Clone + Send> Send for CursorMut<'_, T, A> {}
+
+unsafe impl<T: Sync, A: Allocator + Clone + Sync> Sync for CursorMut<'_, T, A> {}
+
+impl<T: fmt::Debug, A: Allocator + Clone> fmt::Debug for CursorMut<'_, T, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("CursorMut").field(&self.list).field(&self.index()).finish()
+    }
+}
+
+impl<'a, T, A: Allocator + Clone> CursorMut<'a, T, A> {
+    /// Returns the index of the cursor or `None` if the cursor is pointing to the "ghost" non-element.
+    pub fn index(&self) -> Option<usize> {
+        self.pos.node.map(|_| self.pos.index)
+    }
+
+    /// Moves the cursor to the next element.
+    /// The cursor moves to the "ghost" non-element after the last element
+    /// and to the first element after the "ghost" non-element.
+    pub fn move_next(&mut self) {
+        self.pos.move_next(self.list)
+    }
+
+    /// Moves the cursor to the previous element.
+    /// The cursor moves to the "ghost" non-element before the first element
+    /// and to the last element before the "ghost" non-element.
+    pub fn move_prev(&mut self) {
+        self.pos.move_prev(self.list)
+    }
+
+    /// Returns a mutable reference to the element that the cursor is currently pointing to.
+    pub fn current(&mut self) -> Option<&mut T> {
+        unsafe { self.pos.current().map(|p| elem_mut(p)) }
+    }
+
+    /// Returns a mutable reference to the next element.
+    /// If the cursor is pointing to the "ghost" non-element then this returns the first element.
+    pub fn peek_next(&mut self) -> Option<&mut T> {
+        unsafe { Position::next_of(self.list, self.pos.current()).map(|p| elem_mut(p)) }
+    }
+
+    /// Returns a mutable reference to the previous element.
+    /// If the cursor is pointing to the "ghost" non-element then this returns the last element.
+    pub fn peek_prev(&mut self) -> Option<&mut T> {
+        unsafe { Position::prev_of(self.list, self.pos.current()).map(|p| elem_mut(p)) }
+    }
+
+    /// Returns a read-only cursor pointing to the current element.
+    pub fn as_cursor(&self) -> Cursor<'_, T, A> {
+        Cursor { pos: self.pos, list: self.list }
+    }
+
+    /// Inserts a new element right after the current one.
+    /// If the cursor is pointing to the "ghost" non-element then the new element is inserted
+    /// at the front of the list.
+    pub fn insert_after(&mut self, el: T) {
+        unsafe {
+            match self.pos.current() {
+                Some((node, offset)) => {
+                    self.list.insert_into(node, offset + 1, el);
+                    self.pos.normalize();
+                    self.list.len += 1;
+                }
+                None => {
+                    match self.list.head {
+                        Some(head) => {
+                            self.list.insert_into(head, 0, el);
+                            self.list.len += 1;
+                        }
+                        None => self.list.push(el),
+                    }
+                    self.pos.index = self.list.len;
+                }
+            }
+        }
+        self.list.refresh_index();
+    }
+
+    /// Inserts a new element right before the current one.
+    /// If the cursor is pointing to the "ghost" non-element then the new element is inserted
+    /// at the end of the list.
+    pub fn insert_before(&mut self, el: T) {
+        unsafe {
+            match self.pos.current() {
+                Some((node, offset)) => {
+                    self.list.insert_into(node, offset, el);
+                    self.pos.offset += 1;
+                    self.pos.normalize();
+                    self.list.len += 1;
+                }
+                None => self.list.push(el),
+            }
+            self.pos.index += 1;
+        }
+        self.list.refresh_index();
+    }
+
+    /// Removes the current element and returns it.
+    /// The cursor is moved to the next element or to the "ghost" non-element if the removed element was the last one.
+    /// If the cursor is pointing to the "ghost" non-element then nothing is removed and `None` is returned.
+    pub fn remove_current(&mut self) -> Option<T> {
+        let (node, offset) = self.pos.current()?;
+        unsafe {
+            let next_node = node.as_ref().next;
+            let el = (*node.as_ptr()).data.remove(offset);
+            self.list.len -= 1;
+            if node.as_ref().data.is_empty() {
+                self.list.steal_some(node);
+                self.pos.node = next_node;
+                self.pos.offset = 0;
+            } else {
+                self.steal_some(node);
+                self.pos.normalize();
+            }
+            self.list.refresh_index();
+            Some(el)
+        }
+    }
+
+    /// Splits the list into two after the current element.
+    /// Returns a new list consisting of everything after the cursor, the original list keeps everything before.
+    /// If the cursor is pointing to the "ghost" non-element then the entire contents of the list are moved.
+    pub fn split_after(&mut self) -> UnrolledLinkedList<T, A> {
+        unsafe {
+            match self.pos.current() {
+                Some((node, offset)) => {
+                    if offset + 1 < node.as_ref().data.len() {
+                        self.list.split_node_at(node, offset + 1);
+                    }
+                    let back = self.list.empty_like();
+                    let mut back = self.list.split_chain_after(node, self.pos.index + 1, back);
+                    if let Some(head) = back.head {
+                        back.steal_some(head);
+                    }
+                    self.list.refresh_index();
+                    back.refresh_index();
+                    back
+                }
+                None => self.take_all(),
+            }
+        }
+    }
+
+    /// Splits the list into two before the current element.
+    /// Returns a new list consisting of everything before the cursor, the original list keeps everything after.
+    /// If the cursor is pointing to the "ghost" non-element then the entire contents of the list are moved.
+    pub fn split_before(&mut self) -> UnrolledLinkedList<T, A> {
+        unsafe {
+            match self.pos.current() {
+                Some((mut node, offset)) => {
+                    if offset > 0 {
+                        node = self.list.split_node_at(node, offset);
+                        self.pos.node = Some(node);
+                        self.pos.offset = 0;
+                    }
+                    let mut front = self.list.split_chain_before(node, self.pos.index);
+                    self.pos.index = 0;
+                    self.list.steal_some(node);
+                    self.list.refresh_index();
+                    front.refresh_index();
+                    front
+                }
+                None => self.take_all(),
+            }
+        }
+    }
+
+    /// Inserts the elements of the given list right after the current one.
+    /// If the cursor is pointing to the "ghost" non-element then the elements are inserted
+    /// at the front of the list.
+    /// The nodes of the given list holding more elements than the capacity of the list are split.
+    pub fn splice_after(&mut self, mut list: UnrolledLinkedList<T, A>) {
+        list.fit_nodes(self.list.cap);
+        if let Some((first, last, len)) = list.take_chain() {
+            unsafe {
+                match self.pos.current() {
+                    Some((node, offset)) => {
+                        if offset + 1 < node.as_ref().data.len() {
+                            self.list.split_node_at(node, offset + 1);
+                        }
+                        self.list.link_chain_after(Some(node), first, last);
+                        self.list.len += len;
+                        self.list.steal_some(last);
+                        self.steal_some(node);
+                    }
+                    None => {
+                        self.list.link_chain_after(None, first, last);
+                        self.list.len += len;
+                        self.pos.index = self.list.len;
+                        self.list.steal_some(last);
+                    }
+                }
+            }
+            self.list.refresh_index();
+        }
+    }
+
+    /// Inserts the elements of the given list right before the current one.
+    /// If the cursor is pointing to the "ghost" non-element then the elements are inserted
+    /// at the end of the list.
+    /// The nodes of the given list holding more elements than the capacity of the list are split.
+    pub fn splice_before(&mut self, mut list: UnrolledLinkedList<T, A>) {
+        list.fit_nodes(self.list.cap);
+        if let Some((first, last, len)) = list.take_chain() {
+            unsafe {
+                let prev = match self.pos.current() {
+                    Some((node, offset)) if offset > 0 => {
+                        self.pos.node = Some(self.list.split_node_at(node, offset));
+                        self.pos.offset = 0;
+                        Some(node)
+                    }
+                    Some((node, _)) => node.as_ref().prev,
+                    None => self.list.tail,
+                };
+                self.list.link_chain_after(prev, first, last);
+                self.list.len += len;
+                self.pos.index += len;
+                self.steal_some(last);
+                if let Some(prev) = prev {
+                    self.steal_some(prev);
+                }
+            }
+            self.list.refresh_index();
+        }
+    }
+
+    fn take_all(&mut self) -> UnrolledLinkedList<T, A> {
+        self.pos.index = 0;
+        let empty = self.list.empty_like();
+        mem::replace(self.list, empty)
+    }
+
+    /// Rebalances the node keeping the cursor at the same element.
+    /// Only the node and its neighbours are touched, so the cursor needs fixing
+    /// only if it is pointing into the node or the next one.
+    unsafe fn steal_some(&mut self, node: NonNull<Node<T, A>>) {
+        // the offset of the current element counting from the first element of the node
+        let rel = match self.pos.node {
+            Some(current) if current == node => Some(self.pos.offset),
+            Some(current) if Some(current) == node.as_ref().next => Some(node.as_ref().data.len() + self.pos.offset),
+            _ => None,
+        };
+        // the node can be freed here, it is not touched after the call
+        if let (Some(rel), Some((first, offset))) = (rel, self.list.steal_some(node)) {
+            self.pos.node = Some(first);
+            self.pos.offset = offset + rel;
+            self.pos.normalize();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::string::ToString;
+    use std::vec;
+    use std::vec::Vec;
+    use crate::UnrolledLinkedList;
+    use crate::tests::check_invariants;
+    use crate::policy::RebalancePolicy;
+
+    fn list_of(cap: usize, range: std::ops::Range<i32>) -> UnrolledLinkedList<i32> {
+        let mut list = UnrolledLinkedList::with_capacity(cap);
+        for el in range {
+            list.push(el);
+        }
+        list
+    }
+
+    fn to_vec(list: &UnrolledLinkedList<i32>) -> Vec<i32> {
+        check_invariants(list);
+        list.iter().cloned().collect()
+    }
+
+    #[test]
+    fn move_test() {
+        let list = list_of(4, 0..10);
+        let mut cursor = list.cursor_front();
+        for el in 0..10 {
+            assert_eq!(cursor.current(), Some(&el));
+            assert_eq!(cursor.index(), Some(el as usize));
+            cursor.move_next();
+        }
+        assert_eq!(cursor.current(), None);
+        assert_eq!(cursor.index(), None);
+        assert_eq!(cursor.peek_next(), Some(&0));
+        assert_eq!(cursor.peek_prev(), Some(&9));
+        cursor.move_next();
+        assert_eq!(cursor.current(), Some(&0));
+        cursor.move_prev();
+        assert_eq!(cursor.current(), None);
+
+        let mut cursor = list.cursor_back();
+        for el in (0..10).rev() {
+            assert_eq!(cursor.current(), Some(&el));
+            assert_eq!(cursor.index(), Some(el as usize));
+            cursor.move_prev();
+        }
+        assert_eq!(cursor.current(), None);
+
+        let cursor = list.cursor_at(4);
+        assert_eq!(cursor.current(), Some(&4));
+        assert_eq!(cursor.peek_prev(), Some(&3));
+        assert_eq!(cursor.peek_next(), Some(&5));
+        assert_eq!(list.cursor_at(10).current(), None);
+
+        let empty: UnrolledLinkedList<i32> = UnrolledLinkedList::new();
+        let mut cursor = empty.cursor_front();
+        cursor.move_next();
+        assert_eq!(cursor.current(), None);
+        assert_eq!(empty.cursor_back().current(), None);
+    }
+
+    #[test]
+    fn insert_test() {
+        let mut list = list_of(4, 0..10);
+        let mut vec = to_vec(&list);
+        let mut cursor = list.cursor_at_mut(3);
+        for el in 10..30 {
+            cursor.insert_before(el);
+            cursor.insert_after(el + 100);
+        }
+        assert_eq!(cursor.current(), Some(&mut 3));
+        assert_eq!(cursor.index(), Some(23));
+        assert_eq!(cursor.peek_next(), Some(&mut 129));
+        assert_eq!(cursor.peek_prev(), Some(&mut 29));
+        vec.splice(3..4, (10..30).chain(std::iter::once(3)).chain((110..130).rev()));
+        assert_eq!(to_vec(&list), vec);
+
+        let mut list = UnrolledLinkedList::with_capacity(4);
+        let mut cursor = list.cursor_front_mut();
+        cursor.insert_after(1);
+        cursor.insert_after(0);
+        cursor.insert_before(2);
+        cursor.insert_before(3);
+        assert_eq!(cursor.index(), None);
+        cursor.move_next();
+        assert_eq!(cursor.index(), Some(0));
+        assert_eq!(to_vec(&list), vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn remove_test() {
+        let mut list = list_of(4, 0..20);
+        let mut cursor = list.cursor_at_mut(5);
+        for el in 5..15 {
+            assert_eq!(cursor.remove_current(), Some(el));
+            assert_eq!(cursor.index(), Some(5));
+        }
+        assert_eq!(cursor.current(), Some(&mut 15));
+        cursor.move_prev();
+        assert_eq!(cursor.remove_current(), Some(4));
+        assert_eq!(cursor.current(), Some(&mut 15));
+        assert_eq!(to_vec(&list), vec![0, 1, 2, 3, 15, 16, 17, 18, 19]);
+
+        let mut cursor = list.cursor_back_mut();
+        for el in (0..4).chain(15..20).rev() {
+            assert_eq!(cursor.remove_current(), Some(el));
+            assert_eq!(cursor.current(), None);
+            cursor.move_prev();
+        }
+        assert_eq!(cursor.remove_current(), None);
+        assert!(list.is_empty());
+    }
+
+    #[test]
+    fn split_test() {
+        for idx in 0..=20 {
+            let mut list = list_of(4, 0..20);
+            list.set_indexed(idx % 2 == 0);
+            let mut cursor = list.cursor_at_mut(idx);
+            let back = cursor.split_after();
+            if idx == 20 {
+                assert!(list.is_empty());
+                assert_eq!(to_vec(&back), (0..20).collect::<Vec<_>>());
+            } else {
+                assert_eq!(cursor.current(), Some(&mut (idx as i32)));
+                assert_eq!(to_vec(&list), (0..=idx as i32).collect::<Vec<_>>());
+                assert_eq!(to_vec(&back), (idx as i32 + 1..20).collect::<Vec<_>>());
+            }
+
+            let mut list = list_of(4, 0..20);
+            list.set_indexed(idx % 2 == 1);
+            let mut cursor = list.cursor_at_mut(idx);
+            let front = cursor.split_before();
+            assert_eq!(cursor.index(), if idx == 20 { None } else { Some(0) });
+            if idx == 20 {
+                assert!(list.is_empty());
+                assert_eq!(to_vec(&front), (0..20).collect::<Vec<_>>());
+            } else {
+                assert_eq!(cursor.current(), Some(&mut (idx as i32)));
+                assert_eq!(to_vec(&front), (0..idx as i32).collect::<Vec<_>>());
+                assert_eq!(to_vec(&list), (idx as i32..20).collect::<Vec<_>>());
+            }
+        }
+    }
+
+    #[test]
+    fn splice_test() {
+        for idx in 0..=10 {
+            let mut list = list_of(4, 0..10);
+            list.set_indexed(true);
+            let mut cursor = list.cursor_at_mut(idx);
+            cursor.splice_after(list_of(4, 100..107));
+            let current = cursor.current().cloned();
+            let mut vec: Vec<_> = (0..10).collect();
+            let at = if idx == 10 { 0 } else { idx + 1 };
+            vec.splice(at..at, 100..107);
+            assert_eq!(current, if idx == 10 { None } else { Some(idx as i32) });
+            assert_eq!(list.len(), 17);
+            assert_eq!(to_vec(&list), vec);
+
+            let mut list = list_of(4, 0..10);
+            let mut cursor = list.cursor_at_mut(idx);
+            cursor.splice_before(list_of(3, 100..107));
+            assert_eq!(cursor.index(), if idx == 10 { None } else { Some(idx + 7) });
+            assert_eq!(cursor.current().cloned(), if idx == 10 { None } else { Some(idx as i32) });
+            cursor.move_prev();
+            assert_eq!(cursor.current(), Some(&mut 106));
+            let mut vec: Vec<_> = (0..10).collect();
+            vec.splice(idx..idx, 100..107);
+            assert_eq!(to_vec(&list), vec);
+        }
+
+        let mut list = list_of(4, 0..2);
+        let mut cursor = list.cursor_front_mut();
+        cursor.splice_after(UnrolledLinkedList::new());
+        cursor.splice_before(list_of(4, 5..6));
+        assert_eq!(cursor.current(), Some(&mut 0));
+        assert_eq!(to_vec(&list), vec![5, 0, 1]);
+    }
+
+    #[test]
+    fn splice_capacity_test() {
+        for idx in 0..=5 {
+            let mut list = list_of(2, 0..5);
+            list.set_indexed(idx % 2 == 0);
+            let mut cursor = list.cursor_at_mut(idx);
+            cursor.splice_before(list_of(32, 100..133));
+            assert_eq!(cursor.current().cloned(), if idx == 5 { None } else { Some(idx as i32) });
+            cursor.splice_after(list_of(7, 200..215));
+            assert_eq!(cursor.current().cloned(), if idx == 5 { None } else { Some(idx as i32) });
+            check_invariants(&list);
+            let mut vec: Vec<_> = (0..5).collect();
+            vec.splice(idx..idx, 100..133);
+            let at = if idx == 5 { 0 } else { idx + 34 };
+            vec.splice(at..at, 200..215);
+            assert_eq!(to_vec(&list), vec);
+
+            list.push(1000);
+            list.insert(20, 1001);
+            check_invariants(&list);
+        }
+    }
+
+    #[test]
+    fn splice_policies_test() {
+        let policies = [RebalancePolicy::BALANCED, RebalancePolicy::APPEND, RebalancePolicy::BTREE];
+        let strings = |range: std::ops::Range<i32>| range.map(|el| el.to_string()).collect::<Vec<_>>();
+        for policy in policies.iter().copied() {
+            for cap in [2, 3, 6].iter().copied() {
+                for (len, spliced) in [(2, 1), (5, 2), (9, 7)].iter().copied() {
+                    for idx in 0..=len as usize {
+                        let new_list = |range| {
+                            let mut list = UnrolledLinkedList::with_policy(cap, policy);
+                            list.extend(strings(range));
+                            list
+                        };
+                        let expected = |at: usize| {
+                            let mut vec = strings(0..len);
+                            vec.splice(at..at, strings(100..100 + spliced));
+                            vec
+                        };
+                        let current = if idx == len as usize { None } else { Some(idx.to_string()) };
+
+                        let mut list = new_list(0..len);
+                        let mut cursor = list.cursor_at_mut(idx);
+                        cursor.splice_before(new_list(100..100 + spliced));
+                        assert_eq!(cursor.current().cloned(), current);
+                        assert_eq!(cursor.index(), current.as_ref().map(|_| idx + spliced as usize));
+                        cursor.move_prev();
+                        assert_eq!(cursor.current().cloned(), Some((99 + spliced).to_string()));
+                        check_invariants(&list);
+                        assert_eq!(list.iter().cloned().collect::<Vec<_>>(), expected(idx));
+
+                        let mut list = new_list(0..len);
+                        let mut cursor = list.cursor_at_mut(idx);
+                        cursor.splice_after(new_list(100..100 + spliced));
+                        assert_eq!(cursor.current().cloned(), current);
+                        cursor.move_next();
+                        assert_eq!(cursor.current().cloned(), Some("100".to_string()));
+                        check_invariants(&list);
+                        let at = if idx == len as usize { 0 } else { idx + 1 };
+                        assert_eq!(list.iter().cloned().collect::<Vec<_>>(), expected(at));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn mixed_ops_test() {
+        mixed_ops(false, RebalancePolicy::BALANCED);
+        mixed_ops(true, RebalancePolicy::BALANCED);
+        mixed_ops(false, RebalancePolicy::APPEND);
+        mixed_ops(true, RebalancePolicy::BTREE);
+    }
+
+    fn mixed_ops(indexed: bool, policy: RebalancePolicy) {
+        let mut list = UnrolledLinkedList::with_policy(4, policy);
+        list.extend(0..10);
+        list.set_indexed(indexed);
+        let mut vec: Vec<_> = (0..10).collect();
+        let mut cursor = list.cursor_front_mut();
+        let mut idx = 0;
+        let mut seed: usize = 7;
+        for el in 10..400 {
+            seed = seed.wrapping_mul(1103515245).wrapping_add(12345) % (1 << 31);
+            match seed % 6 {
+                0 => {
+                    cursor.move_next();
+                    idx = if idx >= vec.len() { 0 } else { idx + 1 };
+                }
+                1 => {
+                    cursor.move_prev();
+                    idx = if idx == 0 { vec.len() } else { idx - 1 };
+                }
+                2 => {
+                    cursor.insert_before(el);
+                    vec.insert(idx, el);
+                    idx += 1;
+                }
+                3 => {
+                    cursor.insert_after(el);
+                    if idx == vec.len() { vec.insert(0, el); idx += 1; } else { vec.insert(idx + 1, el); }
+                }
+                _ => {
+                    let removed = cursor.remove_current();
+                    assert_eq!(removed, if idx < vec.len() { Some(vec.remove(idx)) } else { None });
+                }
+            }
+            assert_eq!(cursor.index(), if idx < vec.len() { Some(idx) } else { None });
+            assert_eq!(cursor.current().cloned(), vec.get(idx).cloned());
+        }
+        assert_eq!(to_vec(&list), vec);
+    }
+}
+
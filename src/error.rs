@@ -0,0 +1,30 @@
+//! The errors returned by the fallible operations of the list.
+
+use core::fmt;
+
+/// The error of a fallible operation such as [`try_insert`](crate::UnrolledLinkedList::try_insert).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListError {
+    /// The index is out of the bounds allowed by the operation.
+    IndexOutOfBounds {
+        /// The requested index.
+        index: usize,
+        /// The len of the list.
+        len: usize,
+    },
+    /// The memory allocator failed to allocate a node or a node buffer.
+    AllocError,
+}
+
+impl fmt::Display for ListError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ListError::IndexOutOfBounds { index, len } =>
+                write!(f, "index {} is out of bounds of the list with len {}", index, len),
+            ListError::AllocError => f.write_str("memory allocation failed"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ListError {}
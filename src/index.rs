@@ -0,0 +1,455 @@
+use core::ptr::NonNull;
+use crate::Node;
+use allocator_api2::alloc::{Allocator, Global};
+use allocator_api2::collections::TryReserveError;
+use allocator_api2::vec::Vec;
+
+/// The end of a link between the entries.
+const NIL: usize = usize::MAX;
+
+/// The index over the nodes of a list.
+///
+/// It keeps the nodes in a treap ordered as in the list, where every entry knows the number of elements
+/// in its subtree, so the node holding an element with a given index is found in *O*(log(*n*/*cap*)) expected time.
+///
+/// The changes of a node length as well as linking or unlinking a single node are applied
+/// in *O*(log(*n*/*cap*)) expected time. The changes of the structure moving whole chains of nodes
+/// (splitting or joining lists, sorting) only mark the index as dirty and it is rebuilt in *O*(*n*/*cap*) time
+/// at the end of the operation. While the index is dirty the list falls back to walking the nodes from the head.
+///
+/// The entries live in one buffer allocated with the allocator of the list,
+/// the freed slots are chained through the `left` links and reused.
+/// Every node remembers the slot of its entry.
+pub(crate) struct NodeIndex<T, A: Allocator = Global> {
+    entries: Vec<Entry<T, A>, A>,
+    root: usize,
+    free: usize,
+    seed: u32,
+    dirty: bool,
+    #[cfg(test)]
+    pub(crate) rebuilds: usize,
+}
+
+struct Entry<T, A: Allocator> {
+    node: NonNull<Node<T, A>>,
+    len: usize,
+    // the number of elements in the nodes of the subtree
+    sum: usize,
+    left: usize,
+    right: usize,
+    parent: usize,
+    priority: u32,
+}
+
+impl<T, A: Allocator> NodeIndex<T, A> {
+    pub(crate) fn new_in(head: Option<NonNull<Node<T, A>>>, alloc: A) -> Self {
+        let mut index = NodeIndex {
+            entries: Vec::new_in(alloc),
+            root: NIL,
+            free: NIL,
+            seed: 0x9E37_79B9,
+            dirty: false,
+            #[cfg(test)]
+            rebuilds: 0,
+        };
+        index.rebuild(head);
+        index
+    }
+
+    pub(crate) fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    pub(crate) fn invalidate(&mut self) {
+        self.dirty = true;
+    }
+
+    /// Makes room for `additional` more nodes, so neither inserting them nor rebuilding the index
+    /// over as many nodes as it holds entries now allocates.
+    pub(crate) fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        self.entries.try_reserve(additional)
+    }
+
+    /// Collects the nodes starting from the head and builds the treap in *O*(*n*/*cap*) time.
+    ///
+    /// Every node is pushed at the bottom of the right spine, the spine entries with lower priorities become
+    /// its left subtree. The entries leaving the spine are complete, so their sums are final.
+    pub(crate) fn rebuild(&mut self, head: Option<NonNull<Node<T, A>>>) {
+        #[cfg(test)]
+        {
+            self.rebuilds += 1;
+        }
+        self.entries.clear();
+        self.root = NIL;
+        self.free = NIL;
+        let mut last = NIL;
+        let mut next_node = head;
+        while let Some(mut node) = next_node {
+            let len = unsafe { node.as_ref().data.len() };
+            let slot = self.entries.len();
+            let priority = self.next_priority();
+            let mut left = NIL;
+            while last != NIL && self.entries[last].priority < priority {
+                self.fix_sum(last);
+                left = last;
+                last = self.entries[last].parent;
+            }
+            self.entries.push(Entry { node, len, sum: len, left, right: NIL, parent: last, priority });
+            if left != NIL {
+                self.entries[left].parent = slot;
+            }
+            match last {
+                NIL => self.root = slot,
+                parent => self.entries[parent].right = slot,
+            }
+            last = slot;
+            unsafe {
+                node.as_mut().pos = slot;
+                next_node = node.as_ref().next;
+            }
+        }
+        while last != NIL {
+            self.fix_sum(last);
+            last = self.entries[last].parent;
+        }
+        self.dirty = false;
+    }
+
+    /// Applies the current length of the node to the treap.
+    pub(crate) fn update(&mut self, node: NonNull<Node<T, A>>) {
+        let (slot, len) = unsafe { (node.as_ref().pos, node.as_ref().data.len()) };
+        let old_len = core::mem::replace(&mut self.entries[slot].len, len);
+        if len >= old_len {
+            self.add_to_path(slot, len - old_len, true);
+        } else {
+            self.add_to_path(slot, old_len - len, false);
+        }
+    }
+
+    /// Adds the node right after the `prev` one or first if the `prev` is `None`.
+    pub(crate) fn insert_after(&mut self, prev: Option<NonNull<Node<T, A>>>, mut node: NonNull<Node<T, A>>) {
+        let len = unsafe { node.as_ref().data.len() };
+        let priority = self.next_priority();
+        let entry = Entry { node, len, sum: len, left: NIL, right: NIL, parent: NIL, priority };
+        let slot = match self.free {
+            NIL => {
+                self.entries.push(entry);
+                self.entries.len() - 1
+            }
+            slot => {
+                self.free = self.entries[slot].left;
+                self.entries[slot] = entry;
+                slot
+            }
+        };
+        unsafe { node.as_mut().pos = slot; }
+
+        // the new entry becomes the leftmost one in the subtree following the `prev`
+        let (mut parent, mut right) = match prev {
+            Some(prev) => (unsafe { prev.as_ref().pos }, true),
+            None => (self.root, false),
+        };
+        if parent != NIL && right && self.entries[parent].right != NIL {
+            parent = self.entries[parent].right;
+            right = false;
+        }
+        if parent == NIL {
+            self.root = slot;
+            return;
+        }
+        if !right {
+            while self.entries[parent].left != NIL {
+                parent = self.entries[parent].left;
+            }
+        }
+        if right {
+            self.entries[parent].right = slot;
+        } else {
+            self.entries[parent].left = slot;
+        }
+        self.entries[slot].parent = parent;
+        self.add_to_path(parent, len, true);
+
+        while let Some(parent) = self.parent_of(slot) {
+            if self.entries[parent].priority >= priority {
+                break;
+            }
+            self.rotate_up(slot);
+        }
+    }
+
+    /// Removes the node. Its slot is freed for the next insertion.
+    pub(crate) fn remove(&mut self, node: NonNull<Node<T, A>>) {
+        let slot = unsafe { node.as_ref().pos };
+        loop {
+            let Entry { left, right, .. } = self.entries[slot];
+            let child = match (left, right) {
+                (NIL, _) | (_, NIL) => break,
+                (left, right) if self.entries[left].priority > self.entries[right].priority => left,
+                (_, right) => right,
+            };
+            self.rotate_up(child);
+        }
+        let Entry { left, right, parent, len, .. } = self.entries[slot];
+        let child = if left != NIL { left } else { right };
+        if child != NIL {
+            self.entries[child].parent = parent;
+        }
+        self.replace_child(parent, slot, child);
+        self.add_to_path(parent, len, false);
+        self.entries[slot].left = self.free;
+        self.free = slot;
+    }
+
+    /// Finds the node holding the element with the given index.
+    /// Returns the node and the index of its first element.
+    pub(crate) fn find(&self, idx: usize) -> (Option<NonNull<Node<T, A>>>, usize) {
+        let mut current = self.root;
+        let mut rest = idx;
+        while current != NIL {
+            let entry = &self.entries[current];
+            let left_sum = self.sum(entry.left);
+            if rest < left_sum {
+                current = entry.left;
+            } else if rest - left_sum < entry.len {
+                return (Some(entry.node), idx - (rest - left_sum));
+            } else {
+                rest -= left_sum + entry.len;
+                current = entry.right;
+            }
+        }
+        (None, 0)
+    }
+
+    /// Finds the first node not satisfying the predicate, given the nodes satisfying it go first.
+    /// Returns the node and the index of its first element, or `None` and the number of elements.
+    pub(crate) fn partition_point<P>(&self, mut pred: P) -> (Option<NonNull<Node<T, A>>>, usize)
+        where P: FnMut(&NonNull<Node<T, A>>) -> bool {
+        let mut current = self.root;
+        let mut start = 0;
+        let mut found = None;
+        while current != NIL {
+            let entry = &self.entries[current];
+            if pred(&entry.node) {
+                start += self.sum(entry.left) + entry.len;
+                current = entry.right;
+            } else {
+                found = Some((entry.node, start + self.sum(entry.left)));
+                current = entry.left;
+            }
+        }
+        match found {
+            Some((node, start)) => (Some(node), start),
+            None => (None, start),
+        }
+    }
+
+    fn sum(&self, slot: usize) -> usize {
+        if slot == NIL { 0 } else { self.entries[slot].sum }
+    }
+
+    fn fix_sum(&mut self, slot: usize) {
+        let Entry { left, right, len, .. } = self.entries[slot];
+        self.entries[slot].sum = len + self.sum(left) + self.sum(right);
+    }
+
+    fn parent_of(&self, slot: usize) -> Option<usize> {
+        Some(self.entries[slot].parent).filter(|parent| *parent != NIL)
+    }
+
+    /// Adds or subtracts the difference to the sums from the entry up to the root.
+    fn add_to_path(&mut self, mut slot: usize, diff: usize, add: bool) {
+        while slot != NIL {
+            let entry = &mut self.entries[slot];
+            if add {
+                entry.sum += diff;
+            } else {
+                entry.sum -= diff;
+            }
+            slot = entry.parent;
+        }
+    }
+
+    fn replace_child(&mut self, parent: usize, old: usize, new: usize) {
+        if parent == NIL {
+            self.root = new;
+        } else if self.entries[parent].left == old {
+            self.entries[parent].left = new;
+        } else {
+            self.entries[parent].right = new;
+        }
+    }
+
+    /// Moves the entry one level up keeping the order of the entries.
+    fn rotate_up(&mut self, slot: usize) {
+        let parent = self.entries[slot].parent;
+        let grandparent = self.entries[parent].parent;
+        if self.entries[parent].left == slot {
+            let middle = self.entries[slot].right;
+            self.entries[parent].left = middle;
+            if middle != NIL {
+                self.entries[middle].parent = parent;
+            }
+            self.entries[slot].right = parent;
+        } else {
+            let middle = self.entries[slot].left;
+            self.entries[parent].right = middle;
+            if middle != NIL {
+                self.entries[middle].parent = parent;
+            }
+            self.entries[slot].left = parent;
+        }
+        self.entries[parent].parent = slot;
+        self.entries[slot].parent = grandparent;
+        self.replace_child(grandparent, parent, slot);
+        self.entries[slot].sum = self.entries[parent].sum;
+        self.fix_sum(parent);
+    }
+
+    /// A xorshift generator, the priorities only have to be independent of the operations.
+    fn next_priority(&mut self) -> u32 {
+        let mut x = self.seed;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.seed = x;
+        x
+    }
+
+    /// Checks the links, the order, the sums and the priorities against the chain starting from the head.
+    #[cfg(test)]
+    pub(crate) fn check(&self, head: Option<NonNull<Node<T, A>>>) {
+        let mut in_order = alloc::vec![];
+        self.check_subtree(self.root, NIL, &mut in_order);
+        let mut chain = alloc::vec![];
+        let mut next_node = head;
+        while let Some(node) = next_node {
+            chain.push(node);
+            next_node = unsafe { node.as_ref().next };
+        }
+        assert_eq!(in_order, chain);
+    }
+
+    #[cfg(test)]
+    fn check_subtree(&self, slot: usize, parent: usize, in_order: &mut alloc::vec::Vec<NonNull<Node<T, A>>>) -> usize {
+        if slot == NIL {
+            return 0;
+        }
+        let entry = &self.entries[slot];
+        assert_eq!(entry.parent, parent);
+        assert_eq!(unsafe { entry.node.as_ref().pos }, slot);
+        assert_eq!(unsafe { entry.node.as_ref().data.len() }, entry.len);
+        if parent != NIL {
+            assert!(self.entries[parent].priority >= entry.priority);
+        }
+        let left = self.check_subtree(entry.left, slot, in_order);
+        in_order.push(entry.node);
+        let right = self.check_subtree(entry.right, slot, in_order);
+        assert_eq!(entry.sum, left + entry.len + right);
+        entry.sum
+    }
+
+    /// The height of the treap.
+    #[cfg(test)]
+    pub(crate) fn height(&self) -> usize {
+        fn height_of<T, A: Allocator>(index: &NodeIndex<T, A>, slot: usize) -> usize {
+            if slot == NIL {
+                return 0;
+            }
+            let entry = &index.entries[slot];
+            1 + height_of(index, entry.left).max(height_of(index, entry.right))
+        }
+        height_of(self, self.root)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::UnrolledLinkedList;
+
+    #[test]
+    fn find_test() {
+        let mut list = UnrolledLinkedList::with_capacity(4);
+        list.set_indexed(true);
+        for i in 0..50 {
+            list.push(i);
+        }
+        assert!(!list.index.as_ref().unwrap().is_dirty());
+        for i in 0..50 {
+            let (node, start) = list.index.as_ref().unwrap().find(i);
+            unsafe {
+                assert_eq!(node.unwrap().as_ref().data[i - start], i);
+            }
+        }
+        assert!(list.index.as_ref().unwrap().find(50).0.is_none());
+    }
+
+    #[test]
+    fn update_test() {
+        let mut list = UnrolledLinkedList::with_capacity(8);
+        for i in 0..50 {
+            list.push(i);
+        }
+        list.set_indexed(true);
+        for i in 0..50 {
+            if i % 3 == 0 {
+                list.insert(i % list.len(), 100 + i);
+            } else {
+                list.remove(i % list.len());
+            }
+            let index = list.index.as_ref().unwrap();
+            assert!(!index.is_dirty());
+            index.check(list.head);
+            for j in 0..list.len() {
+                let (node, start) = index.find(j);
+                let (linear_node, linear_start) = list.find_node_linear(j);
+                assert_eq!(node, linear_node);
+                assert_eq!(start, linear_start);
+            }
+        }
+    }
+
+    #[test]
+    fn balance_test() {
+        let mut list = UnrolledLinkedList::with_capacity(4);
+        list.set_indexed(true);
+        // Miri runs a shorter list
+        let len = if cfg!(miri) { 600 } else { 20_000 };
+        for i in 0..len {
+            list.push(i);
+            if i % 3 == 0 {
+                list.insert(i / 2, i);
+            }
+        }
+        let index = list.index.as_ref().unwrap();
+        index.check(list.head);
+        // a random treap of ~10k entries is expected to be about 30 levels high
+        assert!(index.height() < 80, "height {}", index.height());
+        list.set_indexed(false);
+        list.set_indexed(true);
+        let index = list.index.as_ref().unwrap();
+        index.check(list.head);
+        assert!(index.height() < 80, "height {}", index.height());
+    }
+
+    #[test]
+    fn no_rebuild_test() {
+        let mut list = UnrolledLinkedList::with_capacity(8);
+        list.set_indexed(true);
+        let len = if cfg!(miri) { 300 } else { 10_000 };
+        for i in 0..len {
+            list.push(i);
+            list.insert(i / 2, i);
+            list.push_front(i);
+            if i % 3 == 0 {
+                list.remove(i);
+                list.pop_front();
+                list.pop();
+            }
+        }
+        let index = list.index.as_ref().unwrap();
+        index.check(list.head);
+        // only the empty index has been built, every split and merge has been applied in place
+        assert_eq!(index.rebuilds, 1);
+    }
+}
@@ -0,0 +1,724 @@
+//! The unrolled linked list storing the elements of a node inline.
+//!
+//! Every node of [`InlineUnrolledLinkedList`] keeps its elements in an array of `N` slots
+//! right next to the links, so a node is one allocation and one contiguous block in memory,
+//! in contrast to [`UnrolledLinkedList`](crate::UnrolledLinkedList) where every node refers to a separate `Vec`.
+//!
+//! # Example
+//! ```rust
+//! use unrolled_linked_list::inline::InlineUnrolledLinkedList;
+//!
+//! let mut list: InlineUnrolledLinkedList<u32, 16> = InlineUnrolledLinkedList::new();
+//! list.push(1);
+//! list.insert(0, 0);
+//! assert_eq!(list.get(1), Some(&1));
+//! assert_eq!(list.iter().cloned().collect::<Vec<_>>(), vec![0, 1]);
+//! ```
+
+use core::ptr::{self, NonNull};
+use core::mem::{self, MaybeUninit};
+use core::marker::PhantomData;
+use core::iter::FusedIterator;
+use core::fmt::{Display, Formatter, Debug};
+use core::{fmt, slice};
+use alloc::boxed::Box;
+
+/// The unrolled linked list with the nodes holding up to `N` elements inline.
+/// `N` should be at least 2.
+pub struct InlineUnrolledLinkedList<T, const N: usize> {
+    len: usize,
+    head: Option<NonNull<InlineNode<T, N>>>,
+    tail: Option<NonNull<InlineNode<T, N>>>,
+    marker: PhantomData<Box<InlineNode<T, N>>>,
+}
+
+unsafe impl<T: Send, const N: usize> Send for InlineUnrolledLinkedList<T, N> {}
+
+unsafe impl<T: Sync, const N: usize> Sync for InlineUnrolledLinkedList<T, N> {}
+
+impl<T, const N: usize> Display for InlineUnrolledLinkedList<T, N> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "inline unrolled linked list: len:{}, cap:{}", self.len, N)
+    }
+}
+
+impl<T: fmt::Debug, const N: usize> Debug for InlineUnrolledLinkedList<T, N> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl<T, const N: usize> Default for InlineUnrolledLinkedList<T, N> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const N: usize> InlineUnrolledLinkedList<T, N> {
+    /// Creates an empty list.
+    /// # Panics
+    /// Panics if `N < 2`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unrolled_linked_list::inline::InlineUnrolledLinkedList;
+    ///
+    /// let list: InlineUnrolledLinkedList<u32, 8> = InlineUnrolledLinkedList::new();
+    /// ```
+    pub fn new() -> Self {
+        assert!(N >= 2, "the node should be able to hold at least 2 elements");
+        InlineUnrolledLinkedList {
+            len: 0,
+            head: None,
+            tail: None,
+            marker: PhantomData,
+        }
+    }
+
+    /// Adds an element last in the list.
+    pub fn push(&mut self, el: T) {
+        unsafe {
+            match self.tail {
+                Some(mut node) if node.as_ref().len < N => node.as_mut().push(el),
+                Some(node) => {
+                    let mut next_node = self.split(node);
+                    next_node.as_mut().push(el);
+                }
+                None => {
+                    let mut node = InlineNode::alloc();
+                    node.as_mut().push(el);
+                    self.head = Some(node);
+                    self.tail = Some(node);
+                }
+            }
+        }
+        self.len += 1;
+    }
+
+    /// Inserts an element at the given index.
+    /// # Panics
+    /// Panics if `index > len`.
+    pub fn insert(&mut self, index: usize, el: T) {
+        if index > self.len {
+            panic!("index {} should be less or equal the len {}", index, self.len)
+        }
+        if index == self.len {
+            return self.push(el);
+        }
+        let (mut node, start_idx) = self.find_node(index).expect("the node should exist");
+        let idx = index - start_idx;
+        unsafe {
+            if node.as_ref().len < N {
+                node.as_mut().insert(idx, el);
+            } else {
+                let mut next_node = self.split(node);
+                let data_len = node.as_ref().len;
+                if idx > data_len {
+                    next_node.as_mut().insert(idx - data_len, el);
+                } else {
+                    node.as_mut().insert(idx, el);
+                }
+            }
+        }
+        self.len += 1;
+    }
+
+    /// Removes the last element from the list and returns it.
+    pub fn pop(&mut self) -> Option<T> {
+        unsafe {
+            let mut node = self.tail?;
+            let popped_value = node.as_mut().pop();
+            if node.as_ref().len == 0 {
+                drop(self.unlink_node(node));
+            }
+            self.len -= 1;
+            popped_value
+        }
+    }
+
+    /// Removes the element at the given index and returns it.
+    /// # Panics
+    /// Panics if `index >= len`.
+    pub fn remove(&mut self, index: usize) -> T {
+        if index >= self.len {
+            panic!("index {} should be less then len {}", index, self.len)
+        }
+        let (mut node, start_idx) = self.find_node(index).expect("the node should exist");
+        unsafe {
+            let rem_element = node.as_mut().remove(index - start_idx);
+            self.steal_some(node);
+            self.len -= 1;
+            rem_element
+        }
+    }
+
+    /// Retrieves the element at the given index.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        let (node, start_idx) = self.find_node(index)?;
+        unsafe { node.as_ref().as_slice().get(index - start_idx) }
+    }
+
+    /// Retrieves the mutable reference to the element at the given index.
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        let (mut node, start_idx) = self.find_node(index)?;
+        unsafe { node.as_mut().as_mut_slice().get_mut(index - start_idx) }
+    }
+
+    /// Returns `true` if the list is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the length of the list.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Removes all elements from the list.
+    pub fn clear(&mut self) {
+        drop(mem::take(self));
+    }
+
+    /// Returns `true` if the list contains an element equal to the given value.
+    pub fn contains(&self, x: &T) -> bool
+        where
+            T: PartialEq<T>,
+    {
+        self.iter().any(|e| e == x)
+    }
+
+    /// Provides a double-ended iterator.
+    pub fn iter(&self) -> Iter<'_, T, N> {
+        Iter {
+            len: self.len,
+            index: 0,
+            head: self.head,
+            back: back_index(self.tail),
+            tail: self.tail,
+            marker: PhantomData,
+        }
+    }
+
+    /// Provides a double-ended mut iterator.
+    pub fn iter_mut(&mut self) -> IterMut<'_, T, N> {
+        IterMut {
+            len: self.len,
+            index: 0,
+            head: self.head,
+            back: back_index(self.tail),
+            tail: self.tail,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T, const N: usize> Drop for InlineUnrolledLinkedList<T, N> {
+    fn drop(&mut self) {
+        struct DropGuard<'a, T, const N: usize>(&'a mut InlineUnrolledLinkedList<T, N>);
+
+        impl<'a, T, const N: usize> Drop for DropGuard<'a, T, N> {
+            fn drop(&mut self) {
+                // Continue the same loop we do below. This only runs when a destructor has
+                // panicked. If another one panics this will abort.
+                while self.0.pop_front_node().is_some() {}
+            }
+        }
+
+        while let Some(node) = self.pop_front_node() {
+            let guard = DropGuard(self);
+            drop(node);
+            mem::forget(guard);
+        }
+    }
+}
+
+impl<T, const N: usize> InlineUnrolledLinkedList<T, N> {
+    #[inline]
+    fn pop_front_node(&mut self) -> Option<Box<InlineNode<T, N>>> {
+        self.head.map(|node| unsafe {
+            let node = self.unlink_node(node);
+            self.len -= node.len;
+            node
+        })
+    }
+
+    #[inline]
+    unsafe fn unlink_node(&mut self, node: NonNull<InlineNode<T, N>>) -> Box<InlineNode<T, N>> {
+        let mut node = Box::from_raw(node.as_ptr());
+        match node.prev {
+            Some(mut prev) => prev.as_mut().next = node.next,
+            None => self.head = node.next,
+        }
+        match node.next {
+            Some(mut next) => next.as_mut().prev = node.prev,
+            None => self.tail = node.prev,
+        }
+        node.prev = None;
+        node.next = None;
+        node
+    }
+
+    /// Moves the second half of the node to a new node linked right after it.
+    #[inline]
+    unsafe fn split(&mut self, mut node: NonNull<InlineNode<T, N>>) -> NonNull<InlineNode<T, N>> {
+        let mut next_node = InlineNode::alloc();
+        let at = node.as_ref().len / 2;
+        node.as_mut().split_off(at, next_node.as_mut());
+
+        let next = node.as_ref().next;
+        next_node.as_mut().prev = Some(node);
+        next_node.as_mut().next = next;
+        match next {
+            Some(mut next) => next.as_mut().prev = Some(next_node),
+            None => self.tail = Some(next_node),
+        }
+        node.as_mut().next = Some(next_node);
+        next_node
+    }
+
+    /// Refills the node from the next one when it is less than half full,
+    /// merging both of them when all elements fit in one node.
+    /// Removes the node if nothing has been left in it.
+    #[inline]
+    unsafe fn steal_some(&mut self, mut node: NonNull<InlineNode<T, N>>) {
+        let len = node.as_ref().len;
+        if len == 0 {
+            drop(self.unlink_node(node));
+        } else if len < N / 2 {
+            if let Some(mut next_node) = node.as_ref().next {
+                if len + next_node.as_ref().len >= N {
+                    node.as_mut().take_front(next_node.as_mut(), N / 2 - len);
+                } else {
+                    let count = next_node.as_ref().len;
+                    node.as_mut().take_front(next_node.as_mut(), count);
+                    drop(self.unlink_node(next_node));
+                }
+            }
+        }
+    }
+
+    fn find_node(&self, idx: usize) -> Option<(NonNull<InlineNode<T, N>>, usize)> {
+        let mut shift = 0;
+        let mut next_node = self.head;
+        unsafe {
+            while let Some(node) = next_node {
+                let shift_end = shift + node.as_ref().len;
+                if idx < shift_end {
+                    return Some((node, shift));
+                }
+                shift = shift_end;
+                next_node = node.as_ref().next;
+            }
+        }
+        None
+    }
+}
+
+struct InlineNode<T, const N: usize> {
+    next: Option<NonNull<InlineNode<T, N>>>,
+    prev: Option<NonNull<InlineNode<T, N>>>,
+    len: usize,
+    data: [MaybeUninit<T>; N],
+}
+
+impl<T, const N: usize> InlineNode<T, N> {
+    fn alloc() -> NonNull<InlineNode<T, N>> {
+        let node = Box::new(InlineNode {
+            next: None,
+            prev: None,
+            len: 0,
+            // an array of `MaybeUninit` does not require initialization
+            data: unsafe { MaybeUninit::<[MaybeUninit<T>; N]>::uninit().assume_init() },
+        });
+        Box::leak(node).into()
+    }
+
+    #[inline]
+    fn ptr(&mut self) -> *mut T {
+        self.data.as_mut_ptr() as *mut T
+    }
+
+    #[inline]
+    fn as_slice(&self) -> &[T] {
+        unsafe { slice::from_raw_parts(self.data.as_ptr() as *const T, self.len) }
+    }
+
+    #[inline]
+    fn as_mut_slice(&mut self) -> &mut [T] {
+        unsafe { slice::from_raw_parts_mut(self.ptr(), self.len) }
+    }
+
+    #[inline]
+    unsafe fn push(&mut self, el: T) {
+        self.ptr().add(self.len).write(el);
+        self.len += 1;
+    }
+
+    #[inline]
+    fn pop(&mut self) -> Option<T> {
+        if self.len == 0 {
+            None
+        } else {
+            self.len -= 1;
+            unsafe { Some(self.ptr().add(self.len).read()) }
+        }
+    }
+
+    #[inline]
+    unsafe fn insert(&mut self, idx: usize, el: T) {
+        let p = self.ptr().add(idx);
+        ptr::copy(p, p.add(1), self.len - idx);
+        p.write(el);
+        self.len += 1;
+    }
+
+    #[inline]
+    unsafe fn remove(&mut self, idx: usize) -> T {
+        let p = self.ptr().add(idx);
+        let el = p.read();
+        ptr::copy(p.add(1), p, self.len - idx - 1);
+        self.len -= 1;
+        el
+    }
+
+    /// Moves the elements starting from `at` to the empty node.
+    #[inline]
+    unsafe fn split_off(&mut self, at: usize, other: &mut Self) {
+        ptr::copy_nonoverlapping(self.ptr().add(at), other.ptr(), self.len - at);
+        other.len = self.len - at;
+        self.len = at;
+    }
+
+    /// Moves first `count` elements of the other node to the end of this one.
+    #[inline]
+    unsafe fn take_front(&mut self, other: &mut Self, count: usize) {
+        // the pointer is taken once, every `ptr` call borrows the node anew
+        let src = other.ptr();
+        ptr::copy_nonoverlapping(src, self.ptr().add(self.len), count);
+        ptr::copy(src.add(count), src, other.len - count);
+        self.len += count;
+        other.len -= count;
+    }
+}
+
+impl<T, const N: usize> Drop for InlineNode<T, N> {
+    fn drop(&mut self) {
+        unsafe { ptr::drop_in_place(self.as_mut_slice()) }
+    }
+}
+
+#[inline]
+fn back_index<T, const N: usize>(tail: Option<NonNull<InlineNode<T, N>>>) -> usize {
+    tail.map(|n| unsafe { n.as_ref().len }).unwrap_or(0)
+}
+
+#[inline]
+unsafe fn step_front<T, const N: usize>(head: &mut Option<NonNull<InlineNode<T, N>>>, index: &mut usize) -> Option<*mut T> {
+    let node = (*head)?.as_ptr();
+    let elem = (ptr::addr_of_mut!((*node).data) as *mut T).add(*index);
+    if *index + 1 >= (*node).len {
+        *index = 0;
+        *head = (*node).next;
+    } else {
+        *index += 1;
+    }
+    Some(elem)
+}
+
+#[inline]
+unsafe fn step_back<T, const N: usize>(tail: &mut Option<NonNull<InlineNode<T, N>>>, back: &mut usize) -> Option<*mut T> {
+    let node = (*tail)?.as_ptr();
+    *back -= 1;
+    let elem = (ptr::addr_of_mut!((*node).data) as *mut T).add(*back);
+    if *back == 0 {
+        *tail = (*node).prev;
+        *back = back_index(*tail);
+    }
+    Some(elem)
+}
+
+/// An iterator over the elements of a `InlineUnrolledLinkedList`.
+///
+/// This `struct` is created by [`InlineUnrolledLinkedList::iter()`].
+pub struct Iter<'a, T, const N: usize> {
+    len: usize,
+    index: usize,
+    head: Option<NonNull<InlineNode<T, N>>>,
+    back: usize,
+    tail: Option<NonNull<InlineNode<T, N>>>,
+    marker: PhantomData<&'a InlineNode<T, N>>,
+}
+
+unsafe impl<T: Sync, const N: usize> Send for Iter<'_, T, N> {}
+
+unsafe impl<T: Sync, const N: usize> Sync for Iter<'_, T, N> {}
+
+impl<T, const N: usize> Clone for Iter<'_, T, N> {
+    fn clone(&self) -> Self {
+        Iter { ..*self }
+    }
+}
+
+impl<'a, T, const N: usize> Iterator for Iter<'a, T, N> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        if self.len == 0 { return None; }
+        self.len -= 1;
+        unsafe { step_front(&mut self.head, &mut self.index).map(|e| &*e) }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T, const N: usize> DoubleEndedIterator for Iter<'a, T, N> {
+    fn next_back(&mut self) -> Option<&'a T> {
+        if self.len == 0 { return None; }
+        self.len -= 1;
+        unsafe { step_back(&mut self.tail, &mut self.back).map(|e| &*e) }
+    }
+}
+
+impl<T, const N: usize> ExactSizeIterator for Iter<'_, T, N> {}
+
+impl<T, const N: usize> FusedIterator for Iter<'_, T, N> {}
+
+/// A mutable iterator over the elements of a `InlineUnrolledLinkedList`.
+///
+/// This `struct` is created by [`InlineUnrolledLinkedList::iter_mut()`].
+pub struct IterMut<'a, T, const N: usize> {
+    len: usize,
+    index: usize,
+    head: Option<NonNull<InlineNode<T, N>>>,
+    back: usize,
+    tail: Option<NonNull<InlineNode<T, N>>>,
+    marker: PhantomData<&'a mut InlineNode<T, N>>,
+}
+
+unsafe impl<T: Send, const N: usize> Send for IterMut<'_, T, N> {}
+
+unsafe impl<T: Sync, const N: usize> Sync for IterMut<'_, T, N> {}
+
+impl<'a, T, const N: usize> Iterator for IterMut<'a, T, N> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<&'a mut T> {
+        if self.len == 0 { return None; }
+        self.len -= 1;
+        unsafe { step_front(&mut self.head, &mut self.index).map(|e| &mut *e) }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T, const N: usize> DoubleEndedIterator for IterMut<'a, T, N> {
+    fn next_back(&mut self) -> Option<&'a mut T> {
+        if self.len == 0 { return None; }
+        self.len -= 1;
+        unsafe { step_back(&mut self.tail, &mut self.back).map(|e| &mut *e) }
+    }
+}
+
+impl<T, const N: usize> ExactSizeIterator for IterMut<'_, T, N> {}
+
+impl<T, const N: usize> FusedIterator for IterMut<'_, T, N> {}
+
+/// An owning iterator over the elements of a `InlineUnrolledLinkedList`.
+///
+/// The iterator takes the nodes one by one from the front and frees each of them once it is consumed.
+pub struct IntoIter<T, const N: usize> {
+    front: Option<(Box<InlineNode<T, N>>, usize)>,
+    delegate: InlineUnrolledLinkedList<T, N>,
+}
+
+impl<T, const N: usize> Iterator for IntoIter<T, N> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        loop {
+            if let Some((node, index)) = self.front.as_mut() {
+                if *index < node.len {
+                    *index += 1;
+                    return unsafe { Some(node.ptr().add(*index - 1).read()) };
+                }
+                let (mut node, _) = self.front.take().expect("the node should exist");
+                // the elements have been moved out already
+                node.len = 0;
+            }
+            self.front = Some((self.delegate.pop_front_node()?, 0));
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let front_len = self.front.as_ref().map(|(node, index)| node.len - index).unwrap_or(0);
+        let len = front_len + self.delegate.len;
+        (len, Some(len))
+    }
+}
+
+impl<T, const N: usize> DoubleEndedIterator for IntoIter<T, N> {
+    fn next_back(&mut self) -> Option<T> {
+        match self.delegate.pop() {
+            Some(el) => Some(el),
+            None => {
+                let (node, index) = self.front.as_mut()?;
+                if *index < node.len { node.pop() } else { None }
+            }
+        }
+    }
+}
+
+impl<T, const N: usize> ExactSizeIterator for IntoIter<T, N> {}
+
+impl<T, const N: usize> FusedIterator for IntoIter<T, N> {}
+
+impl<T, const N: usize> Drop for IntoIter<T, N> {
+    fn drop(&mut self) {
+        if let Some((mut node, index)) = self.front.take() {
+            let len = mem::replace(&mut node.len, 0);
+            unsafe { ptr::drop_in_place(ptr::slice_from_raw_parts_mut(node.ptr().add(index), len - index)) }
+        }
+    }
+}
+
+impl<T, const N: usize> IntoIterator for InlineUnrolledLinkedList<T, N> {
+    type Item = T;
+    type IntoIter = IntoIter<T, N>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter { front: None, delegate: self }
+    }
+}
+
+impl<'a, T, const N: usize> IntoIterator for &'a InlineUnrolledLinkedList<T, N> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T, N>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T, const N: usize> IntoIterator for &'a mut InlineUnrolledLinkedList<T, N> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T, N>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::vec;
+    use std::vec::Vec;
+    use crate::inline::InlineUnrolledLinkedList;
+    use crate::tests::DropCounter;
+    use std::cell::Cell;
+
+    fn node_lens<T, const N: usize>(list: &InlineUnrolledLinkedList<T, N>) -> Vec<usize> {
+        let mut lens = vec![];
+        let mut next_node = list.head;
+        unsafe {
+            while let Some(n) = next_node {
+                lens.push(n.as_ref().len);
+                next_node = n.as_ref().next;
+            }
+        }
+        lens
+    }
+
+    #[test]
+    fn mixed_ops_test() {
+        let mut list: InlineUnrolledLinkedList<usize, 4> = InlineUnrolledLinkedList::new();
+        let mut vec = vec![];
+        let mut seed: usize = 3;
+        for i in 0..300 {
+            seed = seed.wrapping_mul(1103515245).wrapping_add(12345) % (1 << 31);
+            match seed % 5 {
+                0 | 1 => {
+                    let idx = seed % (vec.len() + 1);
+                    list.insert(idx, i);
+                    vec.insert(idx, i);
+                }
+                2 => {
+                    list.push(i);
+                    vec.push(i);
+                }
+                3 if !vec.is_empty() => {
+                    let idx = seed % vec.len();
+                    assert_eq!(list.remove(idx), vec.remove(idx));
+                }
+                _ => assert_eq!(list.pop(), vec.pop()),
+            }
+            let lens = node_lens(&list);
+            assert!(lens.iter().all(|l| *l > 0 && *l <= 4));
+            assert_eq!(lens.iter().sum::<usize>(), list.len());
+            assert_eq!(list.iter().collect::<Vec<_>>(), vec.iter().collect::<Vec<_>>());
+            assert_eq!(list.iter().rev().collect::<Vec<_>>(), vec.iter().rev().collect::<Vec<_>>());
+        }
+        for (idx, el) in list.iter_mut().enumerate() {
+            assert_eq!(*el, vec[idx]);
+            *el += 1;
+        }
+        assert_eq!(list.get_mut(0).cloned(), vec.first().map(|e| e + 1));
+        assert_eq!(list.into_iter().collect::<Vec<_>>(), vec.iter().map(|e| e + 1).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn drop_test() {
+        let drops = Cell::new(0);
+        let mut list: InlineUnrolledLinkedList<_, 4> = InlineUnrolledLinkedList::new();
+        for _ in 0..40 {
+            list.push(DropCounter(&drops));
+        }
+        for _ in 0..5 {
+            list.insert(3, DropCounter(&drops));
+            drop(list.remove(10));
+        }
+        assert_eq!(drops.get(), 5);
+        list.clear();
+        assert_eq!(drops.get(), 45);
+
+        for _ in 0..40 {
+            list.push(DropCounter(&drops));
+        }
+        let mut iter = list.into_iter();
+        for _ in 0..6 {
+            drop(iter.next());
+        }
+        drop(iter.next_back());
+        assert_eq!(drops.get(), 52);
+        assert_eq!(iter.len(), 33);
+        drop(iter);
+        assert_eq!(drops.get(), 85);
+    }
+
+    #[test]
+    fn into_iter_test() {
+        for len in 0..12 {
+            let mut list: InlineUnrolledLinkedList<_, 4> = InlineUnrolledLinkedList::new();
+            for i in 0..len {
+                list.push(i);
+            }
+            let mut iter = list.into_iter();
+            let mut vec = vec![];
+            let mut front = true;
+            while let Some(el) = if front { iter.next() } else { iter.next_back() } {
+                vec.push(el);
+                front = !front;
+            }
+            vec.sort();
+            assert_eq!(vec, (0..len).collect::<Vec<_>>());
+        }
+    }
+}
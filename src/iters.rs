@@ -1,10 +1,17 @@
-use std::ptr::NonNull;
-use std::marker::PhantomData;
+use core::ptr::NonNull;
+use core::marker::PhantomData;
 use crate::{UnrolledLinkedList, Node};
-use std::fmt;
+use crate::slice::Bounds;
+use core::fmt;
+use core::iter::FusedIterator;
+use core::{mem, ptr, slice};
+use allocator_api2::alloc::{Allocator, Global};
+use allocator_api2::boxed::Box;
+use allocator_api2::vec::{self, Vec};
+use core::ops::{Range, RangeBounds};
 
-impl<'a, T> UnrolledLinkedList<T> {
-    /// Provides a forward iterator.
+impl<T, A: Allocator + Clone> UnrolledLinkedList<T, A> {
+    /// Provides a double-ended iterator.
     ///
     /// # Examples
     ///
@@ -24,91 +31,212 @@ impl<'a, T> UnrolledLinkedList<T> {
     /// assert_eq!(iter.next(), Some(&2));
     /// assert_eq!(iter.next(), None);
     /// ```
-    pub fn iter(&self) -> Iter<'a, T> {
+    pub fn iter(&self) -> Iter<'_, T, A> {
         Iter {
             len: self.len,
             index: 0,
             head: self.head,
+            back: back_index(self.tail),
             tail: self.tail,
             marker: Default::default(),
         }
     }
 
-    /// Provides a forward mut iterator.
-   ///
-   /// # Examples
-   ///
-   /// ```
-   ///
-   /// use unrolled_linked_list::UnrolledLinkedList;
-   ///
-   /// let mut list: UnrolledLinkedList<u32> = UnrolledLinkedList::new();
-   ///
-   /// list.push(0);
-   /// list.push(1);
-   /// list.push(2);
-   ///
-   /// for element in list.iter_mut() {
-   ///     *element += 10;
-   /// }
-   ///
-   /// let mut iter = list.iter();
-   /// assert_eq!(iter.next(), Some(&10));
-   /// assert_eq!(iter.next(), Some(&11));
-   /// assert_eq!(iter.next(), Some(&12));
-   /// assert_eq!(iter.next(), None);
-   /// ```
-    pub fn iter_mut(&'a mut self) -> IterMut<'a, T> {
+    /// Provides a double-ended mut iterator.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    ///
+    /// use unrolled_linked_list::UnrolledLinkedList;
+    ///
+    /// let mut list: UnrolledLinkedList<u32> = UnrolledLinkedList::new();
+    ///
+    /// list.push(0);
+    /// list.push(1);
+    /// list.push(2);
+    ///
+    /// for element in list.iter_mut() {
+    ///     *element += 10;
+    /// }
+    ///
+    /// let mut iter = list.iter();
+    /// assert_eq!(iter.next(), Some(&10));
+    /// assert_eq!(iter.next(), Some(&11));
+    /// assert_eq!(iter.next(), Some(&12));
+    /// assert_eq!(iter.next(), None);
+    /// ```
+    pub fn iter_mut(&mut self) -> IterMut<'_, T, A> {
         IterMut {
             len: self.len,
             index: 0,
             head: self.head,
-            delegate: self
+            back: back_index(self.tail),
+            tail: self.tail,
+            marker: Default::default(),
+        }
+    }
+
+    /// Removes the specified range from the list in bulk, returning all removed elements as an iterator.
+    /// If the iterator is dropped before being fully consumed, it drops the remaining removed elements.
+    ///
+    /// The range is cut out of the list right away: the nodes covered by it are unlinked as they are
+    /// and only the two edge nodes are split and rebalanced, so the list stays consistent
+    /// even if the iterator is leaked (the removed elements are leaked along with it then).
+    ///
+    /// # Panics
+    /// Panics if the start of the range is greater than the end or if the end is greater than the len.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unrolled_linked_list::UnrolledLinkedList;
+    ///
+    /// let mut list: UnrolledLinkedList<_> = (0..10).collect();
+    /// let drained: Vec<_> = list.drain(2..8).collect();
+    /// assert_eq!(drained, vec![2, 3, 4, 5, 6, 7]);
+    /// assert_eq!(list.iter().copied().collect::<Vec<_>>(), vec![0, 1, 8, 9]);
+    /// ```
+    pub fn drain<R: RangeBounds<usize>>(&mut self, range: R) -> Drain<'_, T, A> {
+        let Range { start, end } = crate::to_range(range, self.len);
+        let mut back = self.split_off(end);
+        let drained = self.split_off(start);
+        self.append(&mut back);
+        Drain {
+            iter: drained.into_iter(),
+            marker: PhantomData,
         }
     }
 
+    /// Creates an iterator which uses a closure to determine if an element should be removed.
+    ///
+    /// If the closure returns `true`, the element is removed and yielded.
+    /// If the closure returns `false`, the element remains in the list.
+    /// If the iterator is dropped before being fully consumed, the rest of the elements stay in the list.
+    ///
+    /// The buffer of every node is filtered in one pass and the nodes are compacted in one sweep
+    /// when the iterator is dropped. If the iterator is leaked, the elements of the list can be leaked as well.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unrolled_linked_list::UnrolledLinkedList;
+    ///
+    /// let mut list: UnrolledLinkedList<_> = (0..10).collect();
+    /// let evens: Vec<_> = list.extract_if(|el| *el % 2 == 0).collect();
+    /// assert_eq!(evens, vec![0, 2, 4, 6, 8]);
+    /// assert_eq!(list.iter().copied().collect::<Vec<_>>(), vec![1, 3, 5, 7, 9]);
+    /// ```
+    pub fn extract_if<F: FnMut(&mut T) -> bool>(&mut self, pred: F) -> ExtractIf<'_, T, F, A> {
+        let empty = self.empty_like();
+        let chain = mem::replace(self, empty);
+        ExtractIf {
+            node: chain.head,
+            list: self,
+            chain,
+            idx: 0,
+            del: 0,
+            old_len: 0,
+            pred,
+        }
+    }
+}
+
+#[inline]
+fn back_index<T, A: Allocator>(tail: Option<NonNull<Node<T, A>>>) -> usize {
+    tail.map(|n| unsafe { n.as_ref().data.len() }).unwrap_or(0)
+}
+
+/// Moves the front position (the node and the index inside it) to the next element
+/// and returns the position it has been pointing to.
+#[inline]
+unsafe fn step_front<T, A: Allocator>(head: &mut Option<NonNull<Node<T, A>>>, index: &mut usize) -> Option<(NonNull<Node<T, A>>, usize)> {
+    let node = (*head)?;
+    let elem = (node, *index);
+    if *index + 1 >= node.as_ref().data.len() {
+        *index = 0;
+        *head = node.as_ref().next;
+    } else {
+        *index += 1;
+    }
+    Some(elem)
 }
+
+/// Moves the back position (the node and the index after the last element) to the previous element
+/// and returns the position of that element.
+#[inline]
+unsafe fn step_back<T, A: Allocator>(tail: &mut Option<NonNull<Node<T, A>>>, back: &mut usize) -> Option<(NonNull<Node<T, A>>, usize)> {
+    let node = (*tail)?;
+    *back -= 1;
+    let elem = (node, *back);
+    if *back == 0 {
+        *tail = node.as_ref().prev;
+        *back = back_index(*tail);
+    }
+    Some(elem)
+}
+
+#[inline]
+pub(crate) unsafe fn elem<'a, T, A: Allocator>((node, index): (NonNull<Node<T, A>>, usize)) -> &'a T {
+    &*(*node.as_ptr()).data.as_ptr().add(index)
+}
+
+/// The pointer is taken without creating a reference to the whole buffer
+/// so the elements returned earlier stay valid.
+#[inline]
+pub(crate) unsafe fn elem_mut<'a, T, A: Allocator>((node, index): (NonNull<Node<T, A>>, usize)) -> &'a mut T {
+    &mut *(*node.as_ptr()).data.as_mut_ptr().add(index)
+}
+
 /// An iterator over the elements of a `UnrolledLinkedList`.
 ///
 /// This `struct` is created by [`UnrolledLinkedList::iter()`]. See its
 /// documentation for more.
-pub struct Iter<'a, T> {
+pub struct Iter<'a, T, A: Allocator = Global> {
     len: usize,
     index: usize,
-    head: Option<NonNull<Node<T>>>,
-    tail: Option<NonNull<Node<T>>>,
-    marker: PhantomData<&'a Node<T>>,
+    head: Option<NonNull<Node<T, A>>>,
+    back: usize,
+    tail: Option<NonNull<Node<T, A>>>,
+    marker: PhantomData<&'a Node<T, A>>,
 }
 
-impl<T: fmt::Debug> fmt::Debug for Iter<'_, T> {
+impl<'a, T, A: Allocator> Iter<'a, T, A> {
+    /// Creates an iterator over the elements within the bounds.
+    pub(crate) unsafe fn from_bounds(bounds: Bounds<T, A>) -> Self {
+        Iter {
+            len: bounds.len,
+            index: bounds.index,
+            head: bounds.head,
+            back: bounds.back,
+            tail: bounds.tail,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T: fmt::Debug, A: Allocator> fmt::Debug for Iter<'_, T, A> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_tuple("Iter").field(&self.len).finish()
     }
 }
-impl<T> Clone for Iter<'_, T> {
+unsafe impl<T: Sync, A: Allocator + Sync> Send for Iter<'_, T, A> {}
+
+unsafe impl<T: Sync, A: Allocator + Sync> Sync for Iter<'_, T, A> {}
+
+impl<T, A: Allocator> Clone for Iter<'_, T, A> {
     fn clone(&self) -> Self {
         Iter { ..*self }
     }
 }
 
-impl<'a, T> Iterator for Iter<'a, T> {
+impl<'a, T, A: Allocator> Iterator for Iter<'a, T, A> {
     type Item = &'a T;
 
     fn next(&mut self) -> Option<&'a T> {
-        if let Some(n) = self.head {
-            unsafe {
-                let node = &*n.as_ptr();
-                let elem = node.data.get(self.index);
-                if self.index + 1 >= node.data.len() {
-                    self.index = 0;
-                    self.head = node.next;
-                } else {
-                    self.index += 1;
-                }
-                self.len -= 1;
-                elem
-            }
-        } else { None }
+        if self.len == 0 { return None; }
+        self.len -= 1;
+        unsafe { step_front(&mut self.head, &mut self.index).map(|e| elem(e)) }
     }
     #[inline]
     fn size_hint(&self) -> (usize, Option<usize>) {
@@ -116,56 +244,233 @@ impl<'a, T> Iterator for Iter<'a, T> {
     }
 
     #[inline]
-    fn last( self) -> Option<&'a T> {
-        unsafe {
-            match (self.head, self.tail) {
-                (Some(n), None) | (_, Some(n)) => (*n.as_ptr()).data.last(),
-                _ => None
-            }
-        }
+    fn last(mut self) -> Option<&'a T> {
+        self.next_back()
+    }
+}
+
+impl<'a, T, A: Allocator> DoubleEndedIterator for Iter<'a, T, A> {
+    fn next_back(&mut self) -> Option<&'a T> {
+        if self.len == 0 { return None; }
+        self.len -= 1;
+        unsafe { step_back(&mut self.tail, &mut self.back).map(|e| elem(e)) }
     }
 }
 
+impl<T, A: Allocator> ExactSizeIterator for Iter<'_, T, A> {}
+
+impl<T, A: Allocator> FusedIterator for Iter<'_, T, A> {}
+
 /// An owning iterator over the elements of a `UnrolledLinkedList`.
 ///
 /// This `struct` is created by the [`into_iter`] method on [`UnrolledLinkedList`]
 /// (provided by the `IntoIterator` trait). See its documentation for more.
 ///
 /// [`into_iter`]: UnrolledLinkedList::into_iter
-pub struct IntoIter<T> {
-    delegate: UnrolledLinkedList<T>,
+///
+/// The iterator takes the buffers of the nodes one by one and frees each node as soon as
+/// it gets its buffer, so consuming the whole list costs *O*(*n*).
+pub struct IntoIter<T, A: Allocator + Clone = Global> {
+    front: vec::IntoIter<T, A>,
+    delegate: UnrolledLinkedList<T, A>,
+    back: vec::IntoIter<T, A>,
 }
 
-impl<T> Iterator for IntoIter<T> {
+impl<T, A: Allocator + Clone> Iterator for IntoIter<T, A> {
     type Item = T;
 
     #[inline]
     fn next(&mut self) -> Option<T> {
-        if self.delegate.is_empty() { None } else { Some(self.delegate.remove(0)) }
+        loop {
+            if let Some(el) = self.front.next() {
+                return Some(el);
+            }
+            match self.delegate.pop_front_node() {
+                Some(node) => self.front = Box::into_inner(node).data.into_vec().into_iter(),
+                None => return self.back.next(),
+            }
+        }
     }
 
     #[inline]
     fn size_hint(&self) -> (usize, Option<usize>) {
-        (self.delegate.len, Some(self.delegate.len))
+        let len = self.front.len() + self.delegate.len + self.back.len();
+        (len, Some(len))
     }
 }
 
-impl<T> IntoIterator for UnrolledLinkedList<T> {
+impl<T, A: Allocator + Clone> DoubleEndedIterator for IntoIter<T, A> {
+    #[inline]
+    fn next_back(&mut self) -> Option<T> {
+        loop {
+            if let Some(el) = self.back.next_back() {
+                return Some(el);
+            }
+            match self.delegate.pop_back_node() {
+                Some(node) => self.back = Box::into_inner(node).data.into_vec().into_iter(),
+                None => return self.front.next_back(),
+            }
+        }
+    }
+}
+
+impl<T, A: Allocator + Clone> ExactSizeIterator for IntoIter<T, A> {}
+
+impl<T, A: Allocator + Clone> FusedIterator for IntoIter<T, A> {}
+
+impl<T, A: Allocator + Clone> IntoIterator for UnrolledLinkedList<T, A> {
     type Item = T;
-    type IntoIter = IntoIter<T>;
+    type IntoIter = IntoIter<T, A>;
 
-    fn into_iter(self) -> Self::IntoIter {
-        IntoIter { delegate: self }
+    fn into_iter(mut self) -> Self::IntoIter {
+        // the nodes are taken one by one, there is nothing to look up
+        self.set_indexed(false);
+        IntoIter {
+            front: Vec::new_in(self.alloc.clone()).into_iter(),
+            back: Vec::new_in(self.alloc.clone()).into_iter(),
+            delegate: self,
+        }
+    }
+}
+impl<T: fmt::Debug, A: Allocator + Clone> fmt::Debug for IntoIter<T, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("IntoIter")
+            .field(&self.front.as_slice())
+            .field(&self.delegate)
+            .field(&self.back.as_slice())
+            .finish()
+    }
+}
+
+/// A draining iterator over the elements of a `UnrolledLinkedList`.
+///
+/// This `struct` is created by [`UnrolledLinkedList::drain`]. See its documentation for more.
+pub struct Drain<'a, T, A: Allocator + Clone = Global> {
+    iter: IntoIter<T, A>,
+    marker: PhantomData<&'a mut UnrolledLinkedList<T, A>>,
+}
+
+impl<'a, T, A: Allocator + Clone> Iterator for Drain<'a, T, A> {
+    type Item = T;
+
+    #[inline]
+    fn next(&mut self) -> Option<T> {
+        self.iter.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<'a, T, A: Allocator + Clone> DoubleEndedIterator for Drain<'a, T, A> {
+    #[inline]
+    fn next_back(&mut self) -> Option<T> {
+        self.iter.next_back()
     }
 }
-impl<T: fmt::Debug> fmt::Debug for IntoIter<T> {
+
+impl<'a, T, A: Allocator + Clone> ExactSizeIterator for Drain<'a, T, A> {}
+
+impl<'a, T, A: Allocator + Clone> FusedIterator for Drain<'a, T, A> {}
+
+impl<'a, T: fmt::Debug, A: Allocator + Clone> fmt::Debug for Drain<'a, T, A> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.debug_tuple("IntoIter").field(&self.delegate).finish()
+        f.debug_tuple("Drain").field(&self.iter).finish()
     }
 }
-impl<'a, T> IntoIterator for &'a UnrolledLinkedList<T> {
+
+/// An iterator which uses a closure to determine if an element should be removed.
+///
+/// This `struct` is created by [`UnrolledLinkedList::extract_if`]. See its documentation for more.
+pub struct ExtractIf<'a, T, F: FnMut(&mut T) -> bool, A: Allocator + Clone = Global> {
+    list: &'a mut UnrolledLinkedList<T, A>,
+    // the nodes are kept out of the list until the iterator is dropped
+    chain: UnrolledLinkedList<T, A>,
+    node: Option<NonNull<Node<T, A>>>,
+    // the index of the next element to check in the current node
+    idx: usize,
+    // the number of removed elements in the current node
+    del: usize,
+    // the length of the current node before filtering, the buffer len is 0 while the node is filtered
+    old_len: usize,
+    pred: F,
+}
+
+impl<'a, T, F: FnMut(&mut T) -> bool, A: Allocator + Clone> ExtractIf<'a, T, F, A> {
+    /// Shifts the unchecked elements of the current node back over the removed ones
+    /// and restores the len of the buffer.
+    unsafe fn finish_node(&mut self, node: NonNull<Node<T, A>>) {
+        let data = &mut (*node.as_ptr()).data;
+        if self.del > 0 && self.idx < self.old_len {
+            let src = data.as_mut_ptr().add(self.idx);
+            ptr::copy(src, src.sub(self.del), self.old_len - self.idx);
+        }
+        data.set_len(self.old_len - self.del);
+        self.idx = 0;
+        self.del = 0;
+        self.old_len = 0;
+    }
+}
+
+impl<'a, T, F: FnMut(&mut T) -> bool, A: Allocator + Clone> Iterator for ExtractIf<'a, T, F, A> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        unsafe {
+            while let Some(node) = self.node {
+                let data = &mut (*node.as_ptr()).data;
+                if self.idx == 0 && self.del == 0 {
+                    self.old_len = data.len();
+                    data.set_len(0);
+                }
+                while self.idx < self.old_len {
+                    let elems = slice::from_raw_parts_mut(data.as_mut_ptr(), self.old_len);
+                    let i = self.idx;
+                    let removed = (self.pred)(&mut elems[i]);
+                    self.idx += 1;
+                    if removed {
+                        self.del += 1;
+                        return Some(ptr::read(&elems[i]));
+                    } else if self.del > 0 {
+                        let src: *const T = &elems[i];
+                        ptr::copy_nonoverlapping(src, &mut elems[i - self.del], 1);
+                    }
+                }
+                self.finish_node(node);
+                self.node = node.as_ref().next;
+            }
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.chain.len))
+    }
+}
+
+impl<'a, T, F: FnMut(&mut T) -> bool, A: Allocator + Clone> Drop for ExtractIf<'a, T, F, A> {
+    fn drop(&mut self) {
+        if let Some(node) = self.node {
+            if self.old_len > 0 {
+                unsafe { self.finish_node(node) }
+            }
+        }
+        self.chain.compact();
+        mem::swap(self.list, &mut self.chain);
+    }
+}
+
+impl<'a, T: fmt::Debug, F: FnMut(&mut T) -> bool, A: Allocator + Clone> fmt::Debug for ExtractIf<'a, T, F, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ExtractIf").finish()
+    }
+}
+
+impl<'a, T, A: Allocator + Clone> IntoIterator for &'a UnrolledLinkedList<T, A> {
     type Item = &'a T;
-    type IntoIter = Iter<'a, T>;
+    type IntoIter = Iter<'a, T, A>;
 
     fn into_iter(self) -> Self::IntoIter {
         self.iter()
@@ -176,76 +481,349 @@ impl<'a, T> IntoIterator for &'a UnrolledLinkedList<T> {
 ///
 /// This `struct` is created by [`UnrolledLinkedList::iter_mut()`].
 /// See its documentation for more.
-pub struct IterMut<'a,T>{
+pub struct IterMut<'a, T, A: Allocator = Global> {
     len: usize,
     index: usize,
-    head: Option<NonNull<Node<T>>>,
-    delegate: &'a mut UnrolledLinkedList<T>,
+    head: Option<NonNull<Node<T, A>>>,
+    back: usize,
+    tail: Option<NonNull<Node<T, A>>>,
+    marker: PhantomData<&'a mut Node<T, A>>,
+}
+
+impl<'a, T, A: Allocator> IterMut<'a, T, A> {
+    /// Creates an iterator over the elements within the bounds.
+    pub(crate) unsafe fn from_bounds(bounds: Bounds<T, A>) -> Self {
+        IterMut {
+            len: bounds.len,
+            index: bounds.index,
+            head: bounds.head,
+            back: bounds.back,
+            tail: bounds.tail,
+            marker: PhantomData,
+        }
+    }
 }
-impl<'a,T> Iterator for IterMut<'a,T>{
+
+impl<'a, T, A: Allocator> Iterator for IterMut<'a, T, A> {
     type Item = &'a mut T;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if let Some(n) = self.head {
-            unsafe {
-                let node = &mut *n.as_ptr();
-                let elem = node.data.get_mut(self.index);
-                if self.index + 1 >= n.as_ref().data.len() {
-                    self.index = 0;
-                    self.head = node.next;
-                } else {
-                    self.index += 1;
-                }
-                self.len -= 1;
-                elem
-            }
-        } else { None }
+        if self.len == 0 { return None; }
+        self.len -= 1;
+        unsafe { step_front(&mut self.head, &mut self.index).map(|e| elem_mut(e)) }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+
+    #[inline]
+    fn last(mut self) -> Option<Self::Item> {
+        self.next_back()
+    }
+}
+
+impl<'a, T, A: Allocator> DoubleEndedIterator for IterMut<'a, T, A> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 { return None; }
+        self.len -= 1;
+        unsafe { step_back(&mut self.tail, &mut self.back).map(|e| elem_mut(e)) }
     }
 }
-impl<T: fmt::Debug> fmt::Debug for IterMut<'_, T> {
+
+impl<T, A: Allocator> ExactSizeIterator for IterMut<'_, T, A> {}
+
+impl<T, A: Allocator> FusedIterator for IterMut<'_, T, A> {}
+
+unsafe impl<T: Send, A: Allocator + Send> Send for IterMut<'_, T, A> {}
+
+unsafe impl<T: Sync, A: Allocator + Sync> Sync for IterMut<'_, T, A> {}
+
+impl<T: fmt::Debug, A: Allocator> fmt::Debug for IterMut<'_, T, A> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.debug_tuple("IterMut").field(&self.delegate).field(&self.len).finish()
+        f.debug_tuple("IterMut").field(&self.len).finish()
+    }
+}
+
+impl<'a, T, A: Allocator + Clone> IntoIterator for &'a mut UnrolledLinkedList<T, A> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T, A>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::vec;
+    use std::vec::Vec;
     use crate::UnrolledLinkedList;
+    use crate::tests::{DropCounter, check_invariants};
+    use std::cell::Cell;
 
 
     #[test]
     fn iter_test() {
         let mut list = UnrolledLinkedList::with_capacity(4);
-        for i in (1..20).into_iter() {
+        for i in 1..20 {
             list.push(i)
         }
-        let mut idx = 1;
-        for el in list.iter() {
+        for (idx, el) in (1..).zip(list.iter()) {
             assert_eq!(el, &idx);
-            idx += 1;
         }
     }
 
     #[test]
     fn into_iter_test() {
         let mut list = UnrolledLinkedList::with_capacity(4);
-        for i in (1..20).into_iter() {
+        for i in 1..20 {
             list.push(i)
         }
-        let mut idx = 1;
-        for el in list.into_iter() {
+        for (idx, el) in (1..).zip(list) {
             assert_eq!(el, idx);
-            idx += 1;
         }
     }
     #[test]
     fn mut_iter_test() {
         let mut list = UnrolledLinkedList::with_capacity(4);
-        for _ in (1..20).into_iter() {
+        for _ in 1..20 {
             list.push(1)
         }
         for el in list.iter_mut() {
             assert_eq!(el, &mut 1);
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn mut_iter_refs_test() {
+        let mut list = UnrolledLinkedList::with_capacity(4);
+        for i in 0..20 {
+            list.push(i)
+        }
+        let mut refs: Vec<&mut i32> = list.iter_mut().collect();
+        for el in refs.iter_mut() {
+            **el += 1;
+        }
+        assert_eq!(*refs[0], 1);
+        for el in &mut list {
+            *el += 1;
+        }
+        assert_eq!(list.iter().cloned().collect::<Vec<_>>(), (2..22).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn iter_after_steal_test() {
+        let mut list = UnrolledLinkedList::with_capacity(4);
+        for i in 0..20 {
+            list.push(i)
+        }
+        for _ in 0..10 {
+            list.remove(1);
+        }
+        let vec: Vec<_> = list.iter().cloned().collect();
+        assert_eq!(vec, [0].iter().cloned().chain(11..20).collect::<Vec<_>>());
+        assert_eq!(list.iter().size_hint(), (10, Some(10)));
+        assert_eq!(list.iter_mut().size_hint(), (10, Some(10)));
+        assert_eq!(list.into_iter().collect::<Vec<_>>(), vec);
+    }
+
+    #[test]
+    fn double_ended_test() {
+        for len in 0..20 {
+            let mut list = UnrolledLinkedList::with_capacity(4);
+            for i in 0..len {
+                list.push(i)
+            }
+            let vec: Vec<_> = (0..len).collect();
+            assert_eq!(list.iter().rev().cloned().collect::<Vec<_>>(), vec.iter().rev().cloned().collect::<Vec<_>>());
+            assert_eq!(list.iter().len(), len as usize);
+            assert_eq!(list.iter().last(), vec.last());
+
+            for split in 0..=len as usize {
+                let mut iter = list.iter();
+                let mut front = vec![];
+                let mut back = vec![];
+                for _ in 0..split {
+                    front.push(*iter.next().unwrap());
+                }
+                assert_eq!(iter.len(), len as usize - split);
+                while let Some(el) = iter.next_back() {
+                    back.push(*el);
+                }
+                assert_eq!(iter.next(), None);
+                assert_eq!(iter.next_back(), None);
+                back.reverse();
+                front.append(&mut back);
+                assert_eq!(front, vec);
+            }
+        }
+    }
+
+    #[test]
+    fn alternate_ends_test() {
+        let mut list = UnrolledLinkedList::with_capacity(4);
+        for i in 0..15 {
+            list.push(i)
+        }
+        for el in list.iter_mut().rev().step_by(2) {
+            *el = -*el;
+        }
+        let mut iter = list.iter_mut();
+        let mut vec = vec![];
+        while let (Some(f), Some(b)) = (iter.next(), iter.next_back()) {
+            vec.push(*f);
+            vec.push(*b);
+        }
+        assert_eq!(vec, vec![0, -14, 1, 13, -2, -12, 3, 11, -4, -10, 5, 9, -6, -8]);
+        assert_eq!(iter.len(), 0);
+
+        let mut into_iter = list.into_iter();
+        assert_eq!(into_iter.next_back(), Some(-14));
+        assert_eq!(into_iter.next(), Some(0));
+        assert_eq!(into_iter.len(), 13);
+        assert_eq!(into_iter.rev().zip(0..).last(), Some((1, 12)));
+    }
+
+    #[test]
+    fn into_iter_drop_test() {
+        let drops = Cell::new(0);
+        let mut list = UnrolledLinkedList::with_capacity(4);
+        for _ in 0..30 {
+            list.push(DropCounter(&drops));
+        }
+        let mut iter = list.into_iter();
+        for _ in 0..7 {
+            drop(iter.next());
+            drop(iter.next_back());
+        }
+        assert_eq!(drops.get(), 14);
+        assert_eq!(iter.len(), 16);
+        drop(iter);
+        assert_eq!(drops.get(), 30);
+    }
+
+    #[test]
+    fn into_iter_meet_test() {
+        for len in 0..20 {
+            let mut list = UnrolledLinkedList::with_capacity(4);
+            for i in 0..len {
+                list.push(i)
+            }
+            let mut iter = list.into_iter();
+            let mut vec = vec![];
+            let mut front = true;
+            while let Some(el) = if front { iter.next() } else { iter.next_back() } {
+                vec.push(el);
+                front = !front;
+            }
+            assert_eq!(iter.len(), 0);
+            vec.sort();
+            assert_eq!(vec, (0..len).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn drain_test() {
+        for start in 0..=15 {
+            for end in start..=15 {
+                let mut list = UnrolledLinkedList::from_iter_with(4, 0.75, 0..15);
+                list.set_indexed(end % 2 == 0);
+                let drained: Vec<_> = list.drain(start..end).collect();
+                check_invariants(&list);
+                assert_eq!(drained, (start..end).collect::<Vec<_>>());
+                assert_eq!(list, (0..start).chain(end..15).collect());
+            }
+        }
+        let mut list: UnrolledLinkedList<_> = (0..10).collect();
+        assert_eq!(list.drain(..).rev().collect::<Vec<_>>(), (0..10).rev().collect::<Vec<_>>());
+        assert!(list.is_empty());
+        let mut list: UnrolledLinkedList<_> = (0..10).collect();
+        assert_eq!(list.drain(7..).len(), 3);
+        assert_eq!(list.drain(..=2).len(), 3);
+        assert_eq!(list, (3..7).collect());
+    }
+
+    #[test]
+    #[should_panic]
+    fn drain_out_of_bounds_test() {
+        let mut list: UnrolledLinkedList<_> = (0..10).collect();
+        list.drain(5..11);
+    }
+
+    #[test]
+    fn drain_drop_test() {
+        let drops = Cell::new(0);
+        let mut list = UnrolledLinkedList::with_capacity(4);
+        for _ in 0..30 {
+            list.push(DropCounter(&drops));
+        }
+        let mut drain = list.drain(5..25);
+        drop(drain.next());
+        drop(drain.next_back());
+        assert_eq!(drops.get(), 2);
+        drop(drain);
+        assert_eq!(drops.get(), 20);
+        check_invariants(&list);
+        assert_eq!(list.len(), 10);
+        drop(list);
+        assert_eq!(drops.get(), 30);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore = "leaks the drained nodes on purpose")]
+    fn drain_forget_test() {
+        let drops = Cell::new(0);
+        let mut list = UnrolledLinkedList::with_capacity(4);
+        for _ in 0..10 {
+            list.push(DropCounter(&drops));
+        }
+        std::mem::forget(list.drain(2..8));
+        check_invariants(&list);
+        assert_eq!(list.len(), 4);
+        drop(list);
+        assert_eq!(drops.get(), 4);
+    }
+
+    #[test]
+    fn extract_if_test() {
+        for cap in 1..6 {
+            let mut list = UnrolledLinkedList::from_iter_with(cap, 0.8, 0..30);
+            list.set_indexed(cap % 2 == 0);
+            let extracted: Vec<_> = list.extract_if(|el| *el % 3 != 0).collect();
+            check_invariants(&list);
+            assert_eq!(extracted, (0..30).filter(|el| el % 3 != 0).collect::<Vec<_>>());
+            assert_eq!(list, (0..30).filter(|el| el % 3 == 0).collect());
+        }
+
+        let mut list: UnrolledLinkedList<_> = (0..30).collect();
+        let mut iter = list.extract_if(|el| *el % 2 == 0);
+        assert_eq!(iter.next(), Some(0));
+        assert_eq!(iter.next(), Some(2));
+        drop(iter);
+        check_invariants(&list);
+        assert_eq!(list, (1..2).chain(3..30).collect());
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore = "leaks the visited nodes on purpose")]
+    fn extract_if_leak_test() {
+        let drops = Cell::new(0);
+        let mut list = UnrolledLinkedList::with_capacity(4);
+        for _ in 0..10 {
+            list.push(DropCounter(&drops));
+        }
+        let mut calls = 0;
+        let mut iter = list.extract_if(|_| {
+            calls += 1;
+            calls % 2 == 0
+        });
+        drop(iter.next());
+        std::mem::forget(iter);
+        check_invariants(&list);
+        assert!(list.is_empty());
+        assert_eq!(drops.get(), 1);
+    }
+}
+
@@ -35,28 +35,75 @@
 //
 //! [`wiki`]: https://en.wikipedia.org/wiki/Unrolled_linked_list/
 
-use std::ptr::NonNull;
-use std::fmt::{Display, Formatter, Debug};
-use std::fmt;
+#![no_std]
+
+extern crate alloc;
+#[cfg(any(feature = "std", test))]
+extern crate std;
+
+use core::ptr::NonNull;
+use core::fmt::{Display, Formatter, Debug};
+use core::fmt;
+use core::mem;
+use core::cmp::Ordering;
+use core::hash::{Hash, Hasher};
+use core::iter::{self, FromIterator};
+use core::ops::{Bound, Index, IndexMut, Range, RangeBounds};
+use core::marker::PhantomData;
+use crate::buf::NodeBuf;
+use crate::index::NodeIndex;
+use crate::policy::RebalancePolicy;
+use crate::error::ListError;
+use core::alloc::Layout;
+use allocator_api2::alloc::{Allocator, Global};
+use allocator_api2::boxed::Box;
+use alloc::vec::Vec;
 
 pub mod iters;
+pub mod cursor;
+pub mod inline;
+pub mod slice;
+pub mod chunks;
+pub mod sorted;
+pub mod policy;
+pub mod error;
+#[cfg(feature = "serde")]
+pub mod ser;
+#[cfg(feature = "rayon")]
+pub mod par;
+mod buf;
+mod index;
+mod sort;
 
 /// The unrolled linked list. The list that acts like a linked list but has the node structure inside.
-pub struct UnrolledLinkedList<T> {
+pub struct UnrolledLinkedList<T, A: Allocator + Clone = Global> {
     len: usize,
     cap: usize,
-    head: Option<NonNull<Node<T>>>,
-    tail: Option<NonNull<Node<T>>>,
+    policy: RebalancePolicy,
+    head: Option<NonNull<Node<T, A>>>,
+    tail: Option<NonNull<Node<T, A>>>,
+    // Every change of a node length has to be passed to `update_index`,
+    // linking or unlinking a single node updates the index in place
+    // and every other change of the structure has to call `invalidate_index`.
+    index: Option<NodeIndex<T, A>>,
+    // the empty nodes reserved by `try_reserve`, chained through `next`
+    spare: Option<NonNull<Node<T, A>>>,
+    spare_len: usize,
+    alloc: A,
+    marker: PhantomData<Box<Node<T, A>, A>>,
 }
 
+unsafe impl<T: Send, A: Allocator + Clone + Send> Send for UnrolledLinkedList<T, A> {}
 
-impl<T> Display for UnrolledLinkedList<T> {
+unsafe impl<T: Sync, A: Allocator + Clone + Sync> Sync for UnrolledLinkedList<T, A> {}
+
+impl<T, A: Allocator + Clone> Display for UnrolledLinkedList<T, A> {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         write!(f, "unrolled linked list: len:{}, cap:{}", self.len, self.cap)
     }
 }
 
-impl<T: fmt::Debug> Debug for UnrolledLinkedList<T> {
+impl<T: fmt::Debug, A: Allocator + Clone> Debug for UnrolledLinkedList<T, A> {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         f.debug_list().entries(self).finish()
     }
@@ -69,6 +116,119 @@ impl<T> Default for UnrolledLinkedList<T> {
     }
 }
 
+impl<T, A: Allocator + Clone> Index<usize> for UnrolledLinkedList<T, A> {
+    type Output = T;
+
+    /// # Panics
+    /// Panics if `index >= len`.
+    fn index(&self, index: usize) -> &T {
+        let len = self.len;
+        self.get(index).unwrap_or_else(|| panic!("index {} should be less than the len {}", index, len))
+    }
+}
+
+impl<T, A: Allocator + Clone> IndexMut<usize> for UnrolledLinkedList<T, A> {
+    /// # Panics
+    /// Panics if `index >= len`.
+    fn index_mut(&mut self, index: usize) -> &mut T {
+        let len = self.len;
+        self.get_mut(index).unwrap_or_else(|| panic!("index {} should be less than the len {}", index, len))
+    }
+}
+
+impl<T> FromIterator<T> for UnrolledLinkedList<T> {
+    fn from_iter<I: IntoIterator<Item=T>>(iter: I) -> Self {
+        let mut list = Self::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<T, A: Allocator + Clone> Extend<T> for UnrolledLinkedList<T, A> {
+    /// Fills the tail node and then adds the full nodes.
+    fn extend<I: IntoIterator<Item=T>>(&mut self, iter: I) {
+        self.extend_packed(iter, self.cap)
+    }
+}
+
+impl<'a, T: 'a + Copy, A: Allocator + Clone> Extend<&'a T> for UnrolledLinkedList<T, A> {
+    fn extend<I: IntoIterator<Item=&'a T>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().copied())
+    }
+}
+
+impl<T> From<Vec<T>> for UnrolledLinkedList<T> {
+    fn from(vec: Vec<T>) -> Self {
+        vec.into_iter().collect()
+    }
+}
+
+impl<T, const N: usize> From<[T; N]> for UnrolledLinkedList<T> {
+    fn from(array: [T; N]) -> Self {
+        IntoIterator::into_iter(array).collect()
+    }
+}
+
+impl<T: Clone, A: Allocator + Clone> Clone for UnrolledLinkedList<T, A> {
+    /// Clones the list keeping the same layout of the nodes.
+    fn clone(&self) -> Self {
+        let mut list = self.empty_like();
+        list.clone_from(self);
+        list
+    }
+
+    /// Clones the list into the existing one reusing its nodes along with their buffers and elements.
+    fn clone_from(&mut self, source: &Self) {
+        let mut old = mem::replace(self, source.empty_like());
+        let mut next_node = source.head;
+        while let Some(n) = next_node {
+            unsafe {
+                let mut node = match old.pop_front_node() {
+                    Some(node) => node,
+                    None => self.new_node(),
+                };
+                node.data.clone_from(&n.as_ref().data);
+                self.push_back_node(node);
+                next_node = n.as_ref().next;
+            }
+        }
+        self.refresh_index();
+    }
+}
+
+impl<T: PartialEq, A: Allocator + Clone> PartialEq for UnrolledLinkedList<T, A> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other)
+    }
+}
+
+impl<T: Eq, A: Allocator + Clone> Eq for UnrolledLinkedList<T, A> {}
+
+impl<T: PartialOrd, A: Allocator + Clone> PartialOrd for UnrolledLinkedList<T, A> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.iter().partial_cmp(other)
+    }
+}
+
+impl<T: Ord, A: Allocator + Clone> Ord for UnrolledLinkedList<T, A> {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter().cmp(other)
+    }
+}
+
+impl<T: Hash, A: Allocator + Clone> Hash for UnrolledLinkedList<T, A> {
+    /// Feeds the len and then every element one by one, so the hash does not depend on the layout of the nodes
+    /// and the equal lists hash the same.
+    /// The result is not guaranteed to match the hash of a `Vec` with the same elements.
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_usize(self.len);
+        for el in self {
+            el.hash(state);
+        }
+    }
+}
+
 impl<T> UnrolledLinkedList<T> {
     /// The default initiation, setting the size of node to 8.
     /// # Examples
@@ -90,16 +250,150 @@ impl<T> UnrolledLinkedList<T> {
     /// let list: UnrolledLinkedList<u32> = UnrolledLinkedList::with_capacity(4);
     /// ```
     pub fn with_capacity(cap: usize) -> Self {
+        UnrolledLinkedList::with_policy(cap, RebalancePolicy::BALANCED)
+    }
+
+    /// Capacity defines the size of the node, the policy defines how the nodes are split and merged.
+    /// # Panics
+    /// Panics if the fractions of the policy are not in `[0.0, 1.0]`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unrolled_linked_list::UnrolledLinkedList;
+    /// use unrolled_linked_list::policy::RebalancePolicy;
+    ///
+    /// let list: UnrolledLinkedList<u32> = UnrolledLinkedList::with_policy(16, RebalancePolicy::BTREE);
+    /// ```
+    pub fn with_policy(cap: usize, policy: RebalancePolicy) -> Self {
+        UnrolledLinkedList::with_policy_in(cap, policy, Global)
+    }
+
+    /// Creates a list from the elements of the iterator packing every node to the given fill factor.
+    ///
+    /// Every node but the last one gets `ceil(cap * fill_factor)` elements, so the nodes are never split while loading
+    /// and the list takes *O*(*n*) time to build. The lower fill factor leaves the room for the later inserts.
+    /// `FromIterator` and `Extend` pack the nodes fully.
+    /// # Panics
+    /// Panics if `fill_factor` is not in `(0.0, 1.0]`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unrolled_linked_list::UnrolledLinkedList;
+    ///
+    /// let list = UnrolledLinkedList::from_iter_with(8, 0.75, 0..100);
+    /// assert_eq!(list.len(), 100);
+    /// assert_eq!(list.get(42), Some(&42));
+    /// ```
+    pub fn from_iter_with<I: IntoIterator<Item=T>>(cap: usize, fill_factor: f64, iter: I) -> Self {
+        if !(fill_factor > 0.0 && fill_factor <= 1.0) {
+            panic!("fill factor {} should be in (0.0, 1.0]", fill_factor)
+        }
+        let mut list = Self::with_capacity(cap);
+        // rounded up by hand, `f64::ceil` is not available without std
+        let fill = cap as f64 * fill_factor;
+        let mut node_len = fill as usize;
+        if (node_len as f64) < fill {
+            node_len += 1;
+        }
+        list.extend_packed(iter, node_len);
+        list
+    }
+}
+
+impl<T, A: Allocator + Clone> UnrolledLinkedList<T, A> {
+    /// Creates an empty list allocating the nodes and their buffers with the given allocator,
+    /// setting the size of node to 8.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unrolled_linked_list::UnrolledLinkedList;
+    /// use allocator_api2::alloc::Global;
+    ///
+    /// let mut list = UnrolledLinkedList::new_in(Global);
+    /// list.push(1);
+    /// ```
+    pub fn new_in(alloc: A) -> Self {
+        UnrolledLinkedList::with_capacity_in(8, alloc)
+    }
+
+    /// Creates an empty list allocating the nodes and their buffers with the given allocator.
+    /// Capacity defines the size of the node.
+    pub fn with_capacity_in(cap: usize, alloc: A) -> Self {
+        UnrolledLinkedList::with_policy_in(cap, RebalancePolicy::BALANCED, alloc)
+    }
+
+    /// Creates an empty list allocating the nodes and their buffers with the given allocator.
+    /// Capacity defines the size of the node, the policy defines how the nodes are split and merged.
+    /// # Panics
+    /// Panics if the fractions of the policy are not in `[0.0, 1.0]`.
+    pub fn with_policy_in(cap: usize, policy: RebalancePolicy, alloc: A) -> Self {
+        policy.validate();
         UnrolledLinkedList {
             cap,
+            policy,
             len: 0,
             head: None,
             tail: None,
+            index: None,
+            spare: None,
+            spare_len: 0,
+            alloc,
+            marker: PhantomData,
         }
     }
+
+    /// Returns a reference to the underlying allocator.
+    pub fn allocator(&self) -> &A {
+        &self.alloc
+    }
+
+    /// Returns the policy of splitting and merging the nodes.
+    pub fn policy(&self) -> RebalancePolicy {
+        self.policy
+    }
+
+    /// Turns on or off the index over the nodes.
+    ///
+    /// The indexed list finds an element by its position in *O*(log(*n*/*cap*)) expected time
+    /// instead of walking the nodes from the head, which speeds up `get`, `get_mut`, `insert` and `remove` on long lists.
+    /// In return, splitting, merging or unlinking a node costs *O*(log(*n*/*cap*)) expected time more,
+    /// and the operations moving whole chains of nodes, such as `split_off`, `append` or `sort`,
+    /// rebuild the index in *O*(*n*/*cap*) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unrolled_linked_list::UnrolledLinkedList;
+    ///
+    /// let mut list = UnrolledLinkedList::with_capacity(16);
+    /// list.set_indexed(true);
+    /// for el in 0..1000 {
+    ///     list.push(el);
+    /// }
+    /// assert!(list.is_indexed());
+    /// assert_eq!(list.get(500), Some(&500));
+    /// ```
+    pub fn set_indexed(&mut self, indexed: bool) {
+        self.index = if indexed { Some(NodeIndex::new_in(self.head, self.alloc.clone())) } else { None };
+    }
+
+    /// Returns `true` if the list keeps the index over the nodes.
+    pub fn is_indexed(&self) -> bool {
+        self.index.is_some()
+    }
+
+    /// Creates an empty list with the same settings.
+    fn empty_like(&self) -> Self {
+        let mut list = Self::with_policy_in(self.cap, self.policy, self.alloc.clone());
+        list.set_indexed(self.is_indexed());
+        list
+    }
 }
 
-impl<T> UnrolledLinkedList<T> {
+impl<T, A: Allocator + Clone> UnrolledLinkedList<T, A> {
     /// Adds an element last in the list.
     ///
     /// # Examples
@@ -116,22 +410,23 @@ impl<T> UnrolledLinkedList<T> {
     /// assert_eq!(dl.pop().unwrap(), 1);
     /// ```
     pub fn push(&mut self, el: T) {
-        match (self.head, self.tail) {
-            (_, Some(mut node)) | (Some(mut node), None) => {
+        match self.tail {
+            Some(mut node) => {
                 unsafe {
-                    let node = node.as_mut();
-                    if node.is_full(self.cap) {
-                        self.tail = Some(node.split_and_push(el));
-                    } else { node.data.push(el); }
+                    if node.as_ref().is_full(self.cap) {
+                        let mut next_node = self.split(node);
+                        next_node.as_mut().data.push(el);
+                        self.update_index(next_node);
+                    } else {
+                        node.as_mut().data.push(el);
+                        self.update_index(node);
+                    }
                 }
             }
-            (None, None) => {
-                let mut node = Box::new(Node::new());
-                node.data.push(el);
-                self.head = Some(Box::leak(node).into())
-            }
+            None => self.push_first(el)
         }
         self.len += 1;
+        self.refresh_index();
     }
     /// Adds an element last in the list.
     /// # Panics
@@ -151,24 +446,15 @@ impl<T> UnrolledLinkedList<T> {
         if index > self.len {
             panic!("index {} should be less or equal the len {}", index, self.len)
         }
+        if index == self.len {
+            return self.push(el);
+        }
 
-        if let (Some(mut node_ptr), start_idx) = self.find_node(index) {
-            unsafe {
-                let local_idx = index - start_idx;
-                let node = node_ptr.as_mut();
-                if node.is_full(self.cap) {
-                    let next_node = node.split_and_insert(el, local_idx);
-                    if self.tail.is_none() { self.tail = Some(next_node); }
-                } else {
-                    node.data.insert(local_idx, el);
-                }
-            }
-        } else {
-            let mut first_node = Box::new(Node::new());
-            first_node.data.insert(index, el);
-            self.head = Some(Box::leak(first_node).into())
+        if let (Some(node), start_idx) = self.find_node(index) {
+            unsafe { self.insert_into(node, index - start_idx, el); }
         }
         self.len += 1;
+        self.refresh_index();
     }
     /// removes the last element from the list and returns it.
     ///
@@ -184,27 +470,117 @@ impl<T> UnrolledLinkedList<T> {
     /// ```
     pub fn pop(&mut self) -> Option<T> {
         unsafe {
-            return match (self.head, self.tail) {
-                (Some(mut f), None) => {
-                    let first = f.as_mut();
-                    if first.data.is_empty() {
-                        None
-                    } else {
-                        self.len -= 1;
-                        first.data.pop()
-                    }
-                }
-                (_, Some(mut l)) => {
-                    let last = l.as_mut();
-                    let popped_value = last.data.pop();
-                    if last.data.is_empty() { self.unlink_last(); }
-                    self.len -= 1;
-                    popped_value
-                }
-                _ => None
-            };
+            let node = self.tail?;
+            let popped_value = (*node.as_ptr()).data.pop();
+            if node.as_ref().data.is_empty() {
+                drop(self.unlink_node(node));
+            } else {
+                self.update_index(node);
+            }
+            self.len -= 1;
+            self.refresh_index();
+            popped_value
+        }
+    }
+    /// Adds an element first in the list.
+    /// The element goes to the head node if it has room, otherwise a new head node is opened,
+    /// so the full head node is never split.
+    ///
+    /// The node buffers keep the room in front of the first element and a new head node is filled from the back,
+    /// so the elements of the head node are moved only once the room in front is used up.
+    ///
+    /// This operation should compute in *O*(1) amortized time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unrolled_linked_list::UnrolledLinkedList;
+    /// let mut dl = UnrolledLinkedList::new();
+    ///
+    /// dl.push_front(2);
+    /// dl.push_front(1);
+    /// assert_eq!(dl.front(), Some(&1));
+    /// assert_eq!(dl.back(), Some(&2));
+    /// ```
+    pub fn push_front(&mut self, el: T) {
+        match self.head {
+            Some(node) if !unsafe { node.as_ref() }.is_full(self.cap) => unsafe {
+                (*node.as_ptr()).data.push_front(el);
+                self.update_index(node);
+            },
+            Some(_) => unsafe {
+                let mut node = self.new_node();
+                node.data.push_front(el);
+                let node = Self::leak_node(node);
+                self.link_chain_after(None, node, node);
+            },
+            None => self.push_first(el),
+        }
+        self.len += 1;
+        self.refresh_index();
+    }
+    /// Removes the first element from the list and returns it.
+    /// The head node left under-filled is refilled from the next node like in [`remove`](UnrolledLinkedList::remove)
+    /// and it is unlinked once it is drained.
+    ///
+    /// This operation should compute in *O*(1) amortized time,
+    /// as long as the minimum fill of the policy leaves some room in the node.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unrolled_linked_list::UnrolledLinkedList;
+    /// let mut dl: UnrolledLinkedList<_> = vec![1, 2].into();
+    ///
+    /// assert_eq!(dl.pop_front(), Some(1));
+    /// assert_eq!(dl.pop_front(), Some(2));
+    /// assert_eq!(dl.pop_front(), None);
+    /// ```
+    pub fn pop_front(&mut self) -> Option<T> {
+        unsafe {
+            let node = self.head?;
+            let popped_value = (*node.as_ptr()).data.pop_front();
+            self.steal_some(node);
+            self.len -= 1;
+            self.refresh_index();
+            popped_value
         }
     }
+    /// Provides a reference to the first element, or `None` if the list is empty.
+    ///
+    /// This operation should compute in *O*(1) time.
+    pub fn front(&self) -> Option<&T> {
+        self.head.and_then(|node| unsafe { (*node.as_ptr()).data.first() })
+    }
+    /// Provides a mutable reference to the first element, or `None` if the list is empty.
+    ///
+    /// This operation should compute in *O*(1) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unrolled_linked_list::UnrolledLinkedList;
+    /// let mut dl: UnrolledLinkedList<_> = vec![1, 2].into();
+    ///
+    /// *dl.front_mut().unwrap() = 0;
+    /// *dl.back_mut().unwrap() = 3;
+    /// assert_eq!(dl.iter().copied().collect::<Vec<_>>(), vec![0, 3]);
+    /// ```
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        self.head.and_then(|node| unsafe { (*node.as_ptr()).data.first_mut() })
+    }
+    /// Provides a reference to the last element, or `None` if the list is empty.
+    ///
+    /// This operation should compute in *O*(1) time.
+    pub fn back(&self) -> Option<&T> {
+        self.tail.and_then(|node| unsafe { (*node.as_ptr()).data.last() })
+    }
+    /// Provides a mutable reference to the last element, or `None` if the list is empty.
+    ///
+    /// This operation should compute in *O*(1) time.
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        self.tail.and_then(|node| unsafe { (*node.as_ptr()).data.last_mut() })
+    }
     /// removes the custom element from the list accordign to the index and returns it.
     /// # Panics
     /// Panics if `index >= len`.
@@ -219,19 +595,9 @@ impl<T> UnrolledLinkedList<T> {
     /// assert_eq!(dl.remove(0), 0);
     /// ```
     pub fn remove(&mut self, index: usize) -> T {
-        if index >= self.len {
-            panic!("index {} should be less then len {}", index, self.len)
-        }
-        unsafe {
-            if let (Some(mut n), start_idx) = self.find_node(index) {
-                let node = n.as_mut();
-                let rem_element = node.data.remove(index - start_idx);
-                node.steal_some(self.cap);
-                self.len -= 1;
-                rem_element
-            } else {
-                unreachable!("the node should exist");
-            }
+        match self.try_remove(index) {
+            Ok(el) => el,
+            Err(e) => panic!("{}", e),
         }
     }
     /// retrieves the custom element from the list according to the index and returns it.
@@ -250,7 +616,7 @@ impl<T> UnrolledLinkedList<T> {
     pub fn get(&self, index: usize) -> Option<&T> {
         unsafe {
             if let (Some(n), start_idx) = self.find_node(index) {
-                (*n.as_ptr()).data.get(index - start_idx)
+                n.as_ref().data.get(index - start_idx)
             } else { None }
         }
     }
@@ -267,11 +633,10 @@ impl<T> UnrolledLinkedList<T> {
     /// assert_eq!(dl.get_mut(1), Some(&mut 1));
     /// assert_eq!(dl.get_mut(0), Some(&mut 0));
     /// ```
-    pub fn get_mut(&self, index: usize) -> Option<&mut T> {
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
         unsafe {
-            if let (Some(n), start_idx) = self.find_node(index) {
-                let node = &mut *n.as_ptr();
-                node.data.get_mut(index - start_idx)
+            if let (Some(mut n), start_idx) = self.find_node(index) {
+                n.as_mut().data.get_mut(index - start_idx)
             } else { None }
         }
     }
@@ -291,10 +656,11 @@ impl<T> UnrolledLinkedList<T> {
     }
 
     /// Removes all elements from the `LinkedList`.
+    /// Every node is freed and every element is dropped.
     ///
     /// This operation should compute in *O*(*n*) time.
     pub fn clear(&mut self) {
-        *self = Self::with_capacity(self.cap);
+        drop(mem::replace(self, self.empty_like()));
     }
 
     /// Returns `true` if the `LinkedList` contains an element equal to the
@@ -305,133 +671,935 @@ impl<T> UnrolledLinkedList<T> {
     {
         self.iter().any(|e| e == x)
     }
-}
 
-impl<T> UnrolledLinkedList<T> {
-    #[inline]
-    unsafe fn unlink_last(&mut self) {
-        if let Some(mut f) = self.head {
-            let first = f.as_mut();
-            if first.next == self.tail {
-                first.unlink_next();
-                self.tail = None;
-            } else if let Some(mut p) = self.tail.and_then(|mut e| e.as_mut().prev) {
-                let prev_node = p.as_mut();
-                prev_node.unlink_next();
-                self.tail = Some(p);
-            }
+    /// Splits the list into two at the given index.
+    /// Returns everything after the given index, including the index.
+    /// Only the node holding the index is split, the rest of the nodes are moved as they are.
+    ///
+    /// This operation should compute in *O*(*n*/*cap*) time.
+    /// # Panics
+    /// Panics if `at > len`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unrolled_linked_list::UnrolledLinkedList;
+    ///
+    /// let mut list: UnrolledLinkedList<_> = (0..10).collect();
+    /// let back = list.split_off(4);
+    /// assert_eq!(list.iter().copied().collect::<Vec<_>>(), vec![0, 1, 2, 3]);
+    /// assert_eq!(back.iter().copied().collect::<Vec<_>>(), vec![4, 5, 6, 7, 8, 9]);
+    /// ```
+    pub fn split_off(&mut self, at: usize) -> UnrolledLinkedList<T, A> {
+        if at > self.len {
+            panic!("index {} should be less or equal the len {}", at, self.len)
         }
+        self.split_off_into(at, self.empty_like())
     }
-    fn find_node(&self, idx: usize) -> (Option<NonNull<Node<T>>>, usize) {
-        let mut shift = 0;
-        let mut next_node = self.head;
 
-        unsafe {
-            while next_node.is_some() {
-                if let Some(n) = next_node {
-                    let node = n.as_ref();
-                    let shift_end = shift + node.data.len();
-                    if idx >= shift && idx <= shift_end {
-                        return (Some(n), shift);
+    /// Moves the elements starting from `at` to the empty `back` list and returns it.
+    fn split_off_into(&mut self, at: usize, back: Self) -> Self {
+        if at == 0 {
+            return mem::replace(self, back);
+        }
+        let (node, start) = self.find_node(at);
+        let mut back = match node {
+            Some(node) => unsafe {
+                let prev = if at > start {
+                    self.split_node_at(node, at - start);
+                    node
+                } else {
+                    node.as_ref().prev.expect("the node holding a non-zero index has the previous one")
+                };
+                let mut back = self.split_chain_after(prev, at, back);
+                if let Some(head) = back.head {
+                    back.steal_some(head);
+                }
+                back
+            },
+            None => back,
+        };
+        self.refresh_index();
+        back.refresh_index();
+        back
+    }
+
+    /// Moves all elements from `other` to the end of the list, leaving `other` empty.
+    /// The nodes are relinked, only the boundary nodes can be merged.
+    /// The nodes of `other` holding more elements than the capacity of the list are split.
+    ///
+    /// This operation should compute in *O*(1) time, apart from rebuilding the index of the indexed list
+    /// and splitting the nodes of `other` with a larger capacity.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unrolled_linked_list::UnrolledLinkedList;
+    ///
+    /// let mut list: UnrolledLinkedList<_> = (0..3).collect();
+    /// let mut other: UnrolledLinkedList<_> = (3..6).collect();
+    /// list.append(&mut other);
+    /// assert!(other.is_empty());
+    /// assert_eq!(list.iter().copied().collect::<Vec<_>>(), vec![0, 1, 2, 3, 4, 5]);
+    /// ```
+    pub fn append(&mut self, other: &mut Self) {
+        self.insert_list(self.len, other)
+    }
+
+    /// Moves all elements from `other` to the front of the list, leaving `other` empty.
+    /// The nodes are relinked, only the boundary nodes can be merged.
+    /// The nodes of `other` holding more elements than the capacity of the list are split.
+    ///
+    /// This operation should compute in *O*(1) time, apart from rebuilding the index of the indexed list
+    /// and splitting the nodes of `other` with a larger capacity.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unrolled_linked_list::UnrolledLinkedList;
+    ///
+    /// let mut list: UnrolledLinkedList<_> = (3..6).collect();
+    /// let mut other: UnrolledLinkedList<_> = (0..3).collect();
+    /// list.prepend(&mut other);
+    /// assert!(other.is_empty());
+    /// assert_eq!(list.iter().copied().collect::<Vec<_>>(), vec![0, 1, 2, 3, 4, 5]);
+    /// ```
+    pub fn prepend(&mut self, other: &mut Self) {
+        self.insert_list(0, other)
+    }
+
+    /// Rotates the list in-place such that the first `mid` elements move to the end.
+    /// Only the node holding the index `mid` is split, the tail is linked to the head
+    /// and only the nodes on both boundaries can be merged.
+    ///
+    /// This operation should compute in *O*(*n*/*cap* + *cap*) time.
+    /// # Panics
+    /// Panics if `mid > len`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unrolled_linked_list::UnrolledLinkedList;
+    ///
+    /// let mut list: UnrolledLinkedList<_> = (0..6).collect();
+    /// list.rotate_left(2);
+    /// assert_eq!(list.iter().copied().collect::<Vec<_>>(), vec![2, 3, 4, 5, 0, 1]);
+    /// ```
+    pub fn rotate_left(&mut self, mid: usize) {
+        if mid > self.len {
+            panic!("mid {} should be less or equal the len {}", mid, self.len)
+        }
+        if mid == 0 || mid == self.len {
+            return;
+        }
+        let mut back = self.split_off(mid);
+        self.prepend(&mut back);
+    }
+
+    /// Rotates the list in-place such that the last `k` elements move to the front.
+    /// The nodes are relinked as in [`rotate_left`](UnrolledLinkedList::rotate_left).
+    ///
+    /// This operation should compute in *O*(*n*/*cap* + *cap*) time.
+    /// # Panics
+    /// Panics if `k > len`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unrolled_linked_list::UnrolledLinkedList;
+    ///
+    /// let mut list: UnrolledLinkedList<_> = (0..6).collect();
+    /// list.rotate_right(2);
+    /// assert_eq!(list.iter().copied().collect::<Vec<_>>(), vec![4, 5, 0, 1, 2, 3]);
+    /// ```
+    pub fn rotate_right(&mut self, k: usize) {
+        if k > self.len {
+            panic!("k {} should be less or equal the len {}", k, self.len)
+        }
+        self.rotate_left(self.len - k)
+    }
+
+    /// Moves all elements from `other` to the list at the given index, leaving `other` empty.
+    /// The node holding the index is split, the nodes of `other` are relinked
+    /// and only the boundary nodes can be merged.
+    /// The nodes of `other` holding more elements than the capacity of the list are split.
+    ///
+    /// This operation should compute in *O*(*n*/*cap*) time.
+    /// # Panics
+    /// Panics if `index > len`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unrolled_linked_list::UnrolledLinkedList;
+    ///
+    /// let mut list: UnrolledLinkedList<_> = vec![0, 1, 4, 5].into();
+    /// let mut other: UnrolledLinkedList<_> = vec![2, 3].into();
+    /// list.insert_list(2, &mut other);
+    /// assert_eq!(list.iter().copied().collect::<Vec<_>>(), vec![0, 1, 2, 3, 4, 5]);
+    /// ```
+    pub fn insert_list(&mut self, index: usize, other: &mut Self) {
+        if index > self.len {
+            panic!("index {} should be less or equal the len {}", index, self.len)
+        }
+        other.fit_nodes(self.cap);
+        if let Some((first, last, len)) = other.take_chain() {
+            unsafe {
+                // both ends are linked without looking for the node, so appending takes constant time
+                let prev = if index == self.len {
+                    self.tail
+                } else if index == 0 {
+                    None
+                } else {
+                    match self.find_node(index) {
+                        (Some(node), start) if index > start => {
+                            self.split_node_at(node, index - start);
+                            Some(node)
+                        }
+                        (Some(node), _) => node.as_ref().prev,
+                        (None, _) => self.tail,
                     }
-                    shift = shift_end;
-                    next_node = node.next;
+                };
+                self.link_chain_after(prev, first, last);
+                self.len += len;
+                self.steal_some(last);
+                if let Some(prev) = prev {
+                    self.steal_some(prev);
                 }
             }
+            other.refresh_index();
+            self.refresh_index();
         }
-        (None, 0)
+    }
+
+    /// Retains only the elements specified by the predicate.
+    ///
+    /// In other words, removes all elements `e` for which `f(&e)` returns `false`.
+    /// The buffer of every node is filtered in one pass and then the nodes are compacted in one sweep,
+    /// so the operation computes in *O*(*n*) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unrolled_linked_list::UnrolledLinkedList;
+    ///
+    /// let mut list: UnrolledLinkedList<_> = (0..10).collect();
+    /// list.retain(|el| el % 3 == 0);
+    /// assert_eq!(list.iter().copied().collect::<Vec<_>>(), vec![0, 3, 6, 9]);
+    /// ```
+    pub fn retain<F: FnMut(&T) -> bool>(&mut self, mut f: F) {
+        self.retain_mut(|el| f(el))
+    }
+
+    /// Retains only the elements specified by the predicate, passing a mutable reference to it.
+    ///
+    /// In other words, removes all elements `e` for which `f(&mut e)` returns `false`.
+    /// The buffer of every node is filtered in one pass and then the nodes are compacted in one sweep,
+    /// so the operation computes in *O*(*n*) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unrolled_linked_list::UnrolledLinkedList;
+    ///
+    /// let mut list: UnrolledLinkedList<_> = (0..10).collect();
+    /// list.retain_mut(|el| {
+    ///     *el *= 10;
+    ///     *el < 50
+    /// });
+    /// assert_eq!(list.iter().copied().collect::<Vec<_>>(), vec![0, 10, 20, 30, 40]);
+    /// ```
+    pub fn retain_mut<F: FnMut(&mut T) -> bool>(&mut self, mut f: F) {
+        self.extract_if(|el| !f(el)).for_each(drop)
     }
 }
 
-struct Node<T> {
-    next: Option<NonNull<Node<T>>>,
-    prev: Option<NonNull<Node<T>>>,
-    data: Vec<T>,
+impl<T, A: Allocator + Clone> UnrolledLinkedList<T, A> {
+    /// Reserves the memory for at least `additional` more elements to be pushed,
+    /// so the pushes do not allocate until the reserved memory is used up.
+    /// The buffer of the last node is grown to the capacity and the empty nodes are reserved for the rest.
+    ///
+    /// The index over the nodes of the indexed list is grown for the reserved nodes as well.
+    ///
+    /// # Errors
+    /// Returns [`ListError::AllocError`] if the allocator reports a failure.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unrolled_linked_list::UnrolledLinkedList;
+    ///
+    /// let mut list = UnrolledLinkedList::with_capacity(4);
+    /// list.try_reserve(100).expect("out of memory");
+    /// for el in 0..100 {
+    ///     list.push(el);
+    /// }
+    /// ```
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), ListError> {
+        let mut rest = additional;
+        if let Some(tail) = self.tail {
+            let data = unsafe { &mut (*tail.as_ptr()).data };
+            let room = self.cap.saturating_sub(data.len());
+            data.try_reserve_exact(room).map_err(|_| ListError::AllocError)?;
+            rest = rest.saturating_sub(room);
+        }
+        // every new node is split off a full one and takes `split_len` pushes to get full again
+        let per_node = self.policy.split_len(self.cap).max(1);
+        // rounded up by hand, `usize::div_ceil` needs a newer compiler
+        self.try_reserve_nodes(rest.saturating_add(per_node - 1) / per_node)
+    }
+
+    /// Inserts an element at the given index like [`insert`](UnrolledLinkedList::insert), but never panics.
+    /// The node that can be needed for the insertion is allocated beforehand, so the failure leaves the list untouched.
+    ///
+    /// # Errors
+    /// Returns [`ListError::IndexOutOfBounds`] if `index > len`
+    /// and [`ListError::AllocError`] if the allocator reports a failure. The element is dropped then.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unrolled_linked_list::UnrolledLinkedList;
+    /// use unrolled_linked_list::error::ListError;
+    ///
+    /// let mut list = UnrolledLinkedList::new();
+    /// assert_eq!(list.try_insert(0, 1), Ok(()));
+    /// assert_eq!(list.try_insert(5, 2), Err(ListError::IndexOutOfBounds { index: 5, len: 1 }));
+    /// ```
+    pub fn try_insert(&mut self, index: usize, el: T) -> Result<(), ListError> {
+        if index > self.len {
+            return Err(ListError::IndexOutOfBounds { index, len: self.len });
+        }
+        let node = if index == self.len { self.tail } else { self.find_node(index).0 };
+        match node {
+            Some(node) if !unsafe { node.as_ref() }.is_full(self.cap) => {
+                let data = unsafe { &mut (*node.as_ptr()).data };
+                data.try_reserve(1).map_err(|_| ListError::AllocError)?;
+            }
+            _ => self.try_reserve_nodes(1)?,
+        }
+        self.insert(index, el);
+        Ok(())
+    }
+
+    /// Removes the element at the given index like [`remove`](UnrolledLinkedList::remove), but never panics.
+    ///
+    /// # Errors
+    /// Returns [`ListError::IndexOutOfBounds`] if `index >= len`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unrolled_linked_list::UnrolledLinkedList;
+    /// use unrolled_linked_list::error::ListError;
+    ///
+    /// let mut list: UnrolledLinkedList<_> = vec![1, 2].into();
+    /// assert_eq!(list.try_remove(0), Ok(1));
+    /// assert_eq!(list.try_remove(1), Err(ListError::IndexOutOfBounds { index: 1, len: 1 }));
+    /// ```
+    pub fn try_remove(&mut self, index: usize) -> Result<T, ListError> {
+        match self.find_node(index) {
+            (Some(node), start_idx) if index < self.len => unsafe {
+                let rem_element = (*node.as_ptr()).data.remove(index - start_idx);
+                self.steal_some(node);
+                self.len -= 1;
+                self.refresh_index();
+                Ok(rem_element)
+            },
+            _ => Err(ListError::IndexOutOfBounds { index, len: self.len }),
+        }
+    }
+
+    /// Splits the list into two at the given index like [`split_off`](UnrolledLinkedList::split_off), but never panics.
+    /// The node that can be needed for the splitting is allocated beforehand, so the failure leaves the list untouched.
+    ///
+    /// # Errors
+    /// Returns [`ListError::IndexOutOfBounds`] if `at > len`
+    /// and [`ListError::AllocError`] if the allocator reports a failure.
+    pub fn try_split_off(&mut self, at: usize) -> Result<UnrolledLinkedList<T, A>, ListError> {
+        if at > self.len {
+            return Err(ListError::IndexOutOfBounds { index: at, len: self.len });
+        }
+        let mut back = self.empty_like();
+        if let (Some(node), start) = self.find_node(at) {
+            if at > start {
+                self.try_reserve_nodes(1)?;
+            }
+            if let Some(index) = back.index.as_mut().filter(|_| at > 0) {
+                // the index of the back list is rebuilt over the node and all nodes after it
+                let count = iter::successors(Some(node), |node| unsafe { node.as_ref().next }).count();
+                index.try_reserve(count).map_err(|_| ListError::AllocError)?;
+            }
+        }
+        Ok(self.split_off_into(at, back))
+    }
 }
 
-impl<T> Node<T> {
-    fn new() -> Self {
-        Node {
-            next: None,
-            prev: None,
-            data: vec![],
+impl<T, A: Allocator + Clone> Drop for UnrolledLinkedList<T, A> {
+    fn drop(&mut self) {
+        struct DropGuard<'a, T, A: Allocator + Clone>(&'a mut UnrolledLinkedList<T, A>);
+
+        impl<'a, T, A: Allocator + Clone> Drop for DropGuard<'a, T, A> {
+            fn drop(&mut self) {
+                // Continue the same loop we do below. This only runs when a destructor has
+                // panicked. If another one panics this will abort.
+                while self.0.pop_front_node().is_some() {}
+            }
+        }
+
+        self.free_spare();
+        self.index = None;
+        while let Some(node) = self.pop_front_node() {
+            let guard = DropGuard(self);
+            drop(node);
+            mem::forget(guard);
+        }
+    }
+}
+
+// The list keeps the following invariants:
+// - an empty list has neither head nor tail,
+// - a non-empty list has both, they point to the same node if there is only one,
+// - every linked node holds at least one element.
+impl<T, A: Allocator + Clone> UnrolledLinkedList<T, A> {
+    #[inline]
+    fn push_first(&mut self, el: T) {
+        let mut node = self.new_node();
+        node.data.push(el);
+        let node = Self::leak_node(node);
+        self.head = Some(node);
+        self.tail = Some(node);
+        if let Some(index) = self.index.as_mut().filter(|index| !index.is_dirty()) {
+            index.insert_after(None, node);
         }
     }
 
-    unsafe fn unlink_next(&mut self) {
-        if let Some(next) = self.next {
-            let old_next = Box::from_raw(next.as_ptr());
-            let new_next = old_next.next;
-            if let Some(mut new) = new_next {
-                new.as_mut().prev = NonNull::new(self as *mut Node<T>);
+    /// Adds the elements last in the list filling the tail and the new nodes up to `node_len` elements.
+    fn extend_packed<I: IntoIterator<Item=T>>(&mut self, iter: I, node_len: usize) {
+        let node_len = node_len.max(1).min(self.cap.max(1));
+        let mut iter = iter.into_iter();
+        if let Some(mut tail) = self.tail {
+            unsafe {
+                while tail.as_ref().data.len() < node_len {
+                    match iter.next() {
+                        Some(el) => {
+                            tail.as_mut().data.push(el);
+                            self.len += 1;
+                        }
+                        None => break,
+                    }
+                }
+                self.update_index(tail);
+            }
+        }
+        while let Some(first) = iter.next() {
+            let mut node = self.new_node();
+            // the buffer grows with the elements, so a large capacity does not allocate up front
+            node.data.reserve_exact(node_len.min(iter.size_hint().0.saturating_add(1)));
+            node.data.push(first);
+            node.data.extend(iter.by_ref().take(node_len - 1));
+            let filled = node.data.len() == node_len;
+            self.push_back_node(node);
+            if !filled {
+                break;
             }
-            self.next = new_next;
         }
+        self.refresh_index();
     }
-    unsafe fn split(&mut self, mut next: NonNull<Node<T>>) {
-        let len = self.data.len();
-        self.link_next(next);
-        next.as_mut().data = self.data.split_off(len / 2);
+
+    /// Takes a reserved node or allocates a new one.
+    #[inline]
+    fn new_node(&mut self) -> Box<Node<T, A>, A> {
+        match self.spare {
+            Some(node) => unsafe {
+                let mut node = self.own_node(node);
+                self.spare = node.next.take();
+                self.spare_len -= 1;
+                node
+            },
+            None => Box::new_in(Node::new_in(self.alloc.clone()), self.alloc.clone()),
+        }
     }
 
-    fn is_full(&self, cap: usize) -> bool {
-        self.data.len() >= cap
+    /// Gives up the ownership over the node leaving it allocated, to be linked into a chain.
+    #[inline]
+    fn leak_node(node: Box<Node<T, A>, A>) -> NonNull<Node<T, A>> {
+        let (ptr, _) = Box::into_raw_with_allocator(node);
+        unsafe { NonNull::new_unchecked(ptr) }
     }
+
+    /// Takes back the ownership over the node allocated with the allocator of the list.
     #[inline]
-    fn link_next(&mut self, mut next: NonNull<Node<T>>) {
-        unsafe {
-            if let Some(mut old_next) = self.next {
-                old_next.as_mut().prev = Some(next);
-                next.as_mut().next = Some(old_next);
+    unsafe fn own_node(&self, node: NonNull<Node<T, A>>) -> Box<Node<T, A>, A> {
+        Box::from_raw_in(node.as_ptr(), self.alloc.clone())
+    }
+
+    /// Keeps the empty node as reserved.
+    #[inline]
+    fn recycle_node(&mut self, mut node: Box<Node<T, A>, A>) {
+        debug_assert!(node.data.is_empty());
+        node.prev = None;
+        node.next = self.spare;
+        self.spare = Some(Self::leak_node(node));
+        self.spare_len += 1;
+    }
+
+    /// Allocates a node with the buffer for `cap` elements, reporting the allocation failure.
+    fn try_alloc_node(&self) -> Result<Box<Node<T, A>, A>, ListError> {
+        let layout = Layout::new::<Node<T, A>>();
+        let mut node = unsafe {
+            let ptr = self.alloc.allocate(layout).map_err(|_| ListError::AllocError)?;
+            let ptr = ptr.as_ptr() as *mut Node<T, A>;
+            ptr.write(Node::new_in(self.alloc.clone()));
+            Box::from_raw_in(ptr, self.alloc.clone())
+        };
+        node.data.try_reserve_exact(self.cap).map_err(|_| ListError::AllocError)?;
+        Ok(node)
+    }
+
+    /// Makes sure there are at least `count` reserved nodes and the index has room for them.
+    fn try_reserve_nodes(&mut self, count: usize) -> Result<(), ListError> {
+        while self.spare_len < count {
+            let node = self.try_alloc_node()?;
+            self.recycle_node(node);
+        }
+        if let Some(index) = self.index.as_mut() {
+            index.try_reserve(count).map_err(|_| ListError::AllocError)?;
+        }
+        Ok(())
+    }
+
+    /// Frees the reserved nodes.
+    fn free_spare(&mut self) {
+        while let Some(node) = self.spare {
+            unsafe {
+                let node = self.own_node(node);
+                self.spare = node.next;
+            }
+        }
+        self.spare_len = 0;
+    }
+
+    /// Takes the first node out of the list along with its elements.
+    #[inline]
+    fn pop_front_node(&mut self) -> Option<Box<Node<T, A>, A>> {
+        self.head.map(|node| unsafe {
+            let node = self.unlink_node(node);
+            self.len -= node.data.len();
+            node
+        })
+    }
+
+    /// Takes the last node out of the list along with its elements.
+    #[inline]
+    fn pop_back_node(&mut self) -> Option<Box<Node<T, A>, A>> {
+        self.tail.map(|node| unsafe {
+            let node = self.unlink_node(node);
+            self.len -= node.data.len();
+            node
+        })
+    }
+
+    /// Links the node last in the list along with its elements.
+    #[inline]
+    fn push_back_node(&mut self, node: Box<Node<T, A>, A>) {
+        if node.data.is_empty() { return; }
+        self.len += node.data.len();
+        let node = Self::leak_node(node);
+        unsafe { self.link_chain_after(self.tail, node, node); }
+    }
+
+    /// Links the `new` node right after the `node` updating the tail if needed.
+    #[inline]
+    unsafe fn link_after(&mut self, node: NonNull<Node<T, A>>, new: NonNull<Node<T, A>>) {
+        self.link_chain_after(Some(node), new, new)
+    }
+
+    /// Links the chain of nodes from `first` to `last` right after the `node`
+    /// or in front of the list if the `node` is `None`. The len is not changed.
+    #[inline]
+    unsafe fn link_chain_after(&mut self,
+                               node: Option<NonNull<Node<T, A>>>,
+                               mut first: NonNull<Node<T, A>>,
+                               mut last: NonNull<Node<T, A>>) {
+        let next = match node {
+            Some(node) => node.as_ref().next,
+            None => self.head,
+        };
+        first.as_mut().prev = node;
+        last.as_mut().next = next;
+        match node {
+            Some(mut node) => node.as_mut().next = Some(first),
+            None => self.head = Some(first),
+        }
+        match next {
+            Some(mut next) => next.as_mut().prev = Some(last),
+            None => self.tail = Some(last),
+        }
+        match self.index.as_mut().filter(|index| !index.is_dirty()) {
+            Some(index) if first == last => index.insert_after(node, first),
+            Some(index) => index.invalidate(),
+            None => {}
+        }
+    }
+
+    /// Splits the nodes holding more than `cap` elements, so the chain can be linked into a list with a smaller capacity.
+    /// Does nothing if the capacity of the list is not greater.
+    fn fit_nodes(&mut self, cap: usize) {
+        let cap = cap.max(1);
+        if self.cap <= cap {
+            return;
+        }
+        let mut next_node = self.head;
+        while let Some(node) = next_node {
+            unsafe {
+                next_node = node.as_ref().next;
+                let mut len = node.as_ref().data.len();
+                // the last part is split off first, so every element is moved once
+                while len > cap {
+                    len = (len - 1) / cap * cap;
+                    self.split_node_at(node, len);
+                }
+            }
+        }
+    }
+
+    /// Takes the whole chain of nodes out of the list leaving it empty.
+    /// Returns the first node, the last node and the number of elements.
+    #[inline]
+    fn take_chain(&mut self) -> Option<Chain<T, A>> {
+        let len = mem::replace(&mut self.len, 0);
+        self.invalidate_index();
+        match (self.head.take(), self.tail.take()) {
+            (Some(first), Some(last)) => Some((first, last, len)),
+            _ => None,
+        }
+    }
+
+    /// Moves all nodes after the `node` to the empty `back` list created with [`empty_like`](Self::empty_like).
+    /// `kept_len` is the number of elements that stay in the current list.
+    #[inline]
+    unsafe fn split_chain_after(&mut self, mut node: NonNull<Node<T, A>>, kept_len: usize, mut back: Self) -> Self {
+        if let Some(mut next) = node.as_ref().next {
+            next.as_mut().prev = None;
+            node.as_mut().next = None;
+            back.head = Some(next);
+            back.tail = self.tail;
+            back.len = self.len - kept_len;
+            self.tail = Some(node);
+            self.len = kept_len;
+            self.invalidate_index();
+            back.invalidate_index();
+        }
+        back
+    }
+
+    /// Moves all nodes before the `node` to a new list with the same capacity.
+    /// `front_len` is the number of elements that are moved.
+    #[inline]
+    unsafe fn split_chain_before(&mut self, mut node: NonNull<Node<T, A>>, front_len: usize) -> Self {
+        let mut front = self.empty_like();
+        if let Some(mut prev) = node.as_ref().prev {
+            prev.as_mut().next = None;
+            node.as_mut().prev = None;
+            front.head = self.head;
+            front.tail = Some(prev);
+            front.len = front_len;
+            self.head = Some(node);
+            self.len -= front_len;
+            self.invalidate_index();
+            front.invalidate_index();
+        }
+        front
+    }
+
+    /// Unlinks the node from the chain and returns the ownership over it.
+    /// The elements are left untouched and the len is not changed.
+    #[inline]
+    unsafe fn unlink_node(&mut self, node: NonNull<Node<T, A>>) -> Box<Node<T, A>, A> {
+        let mut node = self.own_node(node);
+        match node.prev {
+            Some(mut prev) => prev.as_mut().next = node.next,
+            None => self.head = node.next,
+        }
+        match node.next {
+            Some(mut next) => next.as_mut().prev = node.prev,
+            None => self.tail = node.prev,
+        }
+        node.prev = None;
+        node.next = None;
+        if let Some(index) = self.index.as_mut().filter(|index| !index.is_dirty()) {
+            index.remove(NonNull::from(&*node));
+        }
+        node
+    }
+
+    /// Moves the elements after the split point of the policy to a new node linked right after the node.
+    /// The new node can be empty if the split point keeps all elements in the node.
+    #[inline]
+    unsafe fn split(&mut self, node: NonNull<Node<T, A>>) -> NonNull<Node<T, A>> {
+        let len = node.as_ref().data.len();
+        self.split_node_at(node, self.policy.split_len(len))
+    }
+
+    /// Moves the elements starting from `at` to a new node linked right after the node.
+    #[inline]
+    unsafe fn split_node_at(&mut self, node: NonNull<Node<T, A>>, at: usize) -> NonNull<Node<T, A>> {
+        let mut next_node = self.new_node();
+        next_node.data.append_tail_of(&mut (*node.as_ptr()).data, at);
+        self.update_index(node);
+        let next_node = Self::leak_node(next_node);
+        self.link_after(node, next_node);
+        next_node
+    }
+
+    /// Inserts the element to the node splitting it if the node is full.
+    /// The len is not changed.
+    #[inline]
+    unsafe fn insert_into(&mut self, node: NonNull<Node<T, A>>, idx: usize, el: T) {
+        if node.as_ref().is_full(self.cap) {
+            self.split_and_insert(node, el, idx);
+        } else {
+            (*node.as_ptr()).data.insert(idx, el);
+            self.update_index(node);
+        }
+    }
+
+    #[inline]
+    unsafe fn split_and_insert(&mut self, node: NonNull<Node<T, A>>, el: T, idx: usize) {
+        let next_node = self.split(node);
+        let data = &mut (*node.as_ptr()).data;
+        let next_data = &mut (*next_node.as_ptr()).data;
+        let data_len = data.len();
+        if idx > data_len {
+            next_data.insert(idx - data_len, el);
+        } else {
+            data.insert(idx, el);
+            if next_data.is_empty() {
+                next_data.extend(data.pop());
             }
-            self.next = Some(next);
-            next.as_mut().prev = NonNull::new(self as *mut Node<T>);
         }
+        self.update_index(node);
+        self.update_index(next_node);
     }
 
+    /// Refills the under-filled node from the next one,
+    /// merging both of them when all elements fit in one node,
+    /// or merges the node into the previous one, as the policy allows.
+    /// Removes the node if nothing has been left in it.
+    /// The node is passed to the index since its length is expected to be changed.
+    ///
+    /// Returns the node holding the former first element of the node and the offset of that element in it,
+    /// or `None` if the node was empty and has been removed.
     #[inline]
-    unsafe fn steal_some(&mut self, cap: usize) {
-        if self.data.len() < cap / 2 {
-            if let Some(mut n) = self.next {
-                let next = n.as_mut();
-                if self.data.len() + next.data.len() >= cap {
-                    let diff = cap / 2 - self.data.len();
-                    let mut right = next.data.split_off(diff);
-                    self.data.append(&mut next.data);
-                    next.data.clear();
-                    next.data.append(&mut right);
+    unsafe fn steal_some(&mut self, node: NonNull<Node<T, A>>) -> Option<(NonNull<Node<T, A>>, usize)> {
+        let next_node = node.as_ref().next;
+        if node.as_ref().data.is_empty() {
+            drop(self.unlink_node(node));
+            return None;
+        }
+        // the buffers are borrowed anew after every relinking, since it writes through the nodes
+        let min_len = self.policy.min_len(self.cap);
+        if node.as_ref().data.len() < min_len && self.policy.merge_with_next {
+            if let Some(next_node) = next_node {
+                let data = &mut (*node.as_ptr()).data;
+                let next_data = &mut (*next_node.as_ptr()).data;
+                let diff = min_len - data.len();
+                if data.len() + next_data.len() >= self.cap && next_data.len() > diff {
+                    data.append_front_of(next_data, diff);
+                    self.update_index(next_node);
+                } else {
+                    data.append(next_data);
+                    drop(self.unlink_node(next_node));
+                }
+            }
+        }
+        let len = node.as_ref().data.len();
+        if len < min_len && self.policy.merge_with_prev {
+            if let Some(prev_node) = node.as_ref().prev {
+                let prev_data = &mut (*prev_node.as_ptr()).data;
+                if prev_data.len() + len <= self.cap {
+                    let offset = prev_data.len();
+                    prev_data.append(&mut (*node.as_ptr()).data);
+                    drop(self.unlink_node(node));
+                    self.update_index(prev_node);
+                    return Some((prev_node, offset));
+                }
+            }
+        }
+        self.update_index(node);
+        Some((node, 0))
+    }
+
+    /// Sweeps the nodes once merging the under-full neighbours and freeing the empty nodes,
+    /// so every node but the last one ends up filled at least to the minimum of the policy.
+    /// The len is recounted from the nodes.
+    fn compact(&mut self) {
+        self.invalidate_index();
+        let min_len = self.policy.min_len(self.cap);
+        self.len = 0;
+        let mut next_node = self.head;
+        while let Some(node) = next_node {
+            unsafe {
+                // the buffers are borrowed anew after every relinking, since it writes through the nodes
+                while let Some(next) = node.as_ref().next {
+                    let data = &mut (*node.as_ptr()).data;
+                    let next_data = &mut (*next.as_ptr()).data;
+                    if data.len() + next_data.len() > self.cap {
+                        if data.len() < min_len && !data.is_empty() {
+                            let diff = min_len - data.len();
+                            data.append_front_of(next_data, diff);
+                        }
+                        break;
+                    }
+                    data.append(next_data);
+                    drop(self.unlink_node(next));
+                }
+                next_node = node.as_ref().next;
+                let len = node.as_ref().data.len();
+                if len == 0 {
+                    drop(self.unlink_node(node));
                 } else {
-                    self.data.append(&mut next.data);
-                    self.unlink_next();
+                    self.len += len;
                 }
             }
         }
+        self.refresh_index();
+    }
+
+    /// Passes the changed length of the node to the index.
+    #[inline]
+    fn update_index(&mut self, node: NonNull<Node<T, A>>) {
+        if let Some(index) = self.index.as_mut().filter(|index| !index.is_dirty()) {
+            index.update(node);
+        }
+    }
+
+    #[inline]
+    fn invalidate_index(&mut self) {
+        if let Some(index) = self.index.as_mut() {
+            index.invalidate();
+        }
+    }
+
+    /// Rebuilds the index if the structure has been changed.
+    #[inline]
+    fn refresh_index(&mut self) {
+        let head = self.head;
+        if let Some(index) = self.index.as_mut().filter(|index| index.is_dirty()) {
+            index.rebuild(head);
+        }
+    }
+
+    /// Finds the node holding the element with the given index.
+    /// Returns the node and the index of its first element.
+    fn find_node(&self, idx: usize) -> (Option<NonNull<Node<T, A>>>, usize) {
+        match self.index.as_ref().filter(|index| !index.is_dirty()) {
+            Some(index) => index.find(idx),
+            None => self.find_node_linear(idx),
+        }
+    }
+
+    fn find_node_linear(&self, idx: usize) -> (Option<NonNull<Node<T, A>>>, usize) {
+        let mut shift = 0;
+        let mut next_node = self.head;
+
+        unsafe {
+            while let Some(n) = next_node {
+                let node = n.as_ref();
+                let shift_end = shift + node.data.len();
+                if idx >= shift && idx < shift_end {
+                    return (Some(n), shift);
+                }
+                shift = shift_end;
+                next_node = node.next;
+            }
+        }
+        (None, 0)
+    }
+}
+
+/// Converts the range bounds to the range within `0..=len`.
+/// # Panics
+/// Panics if the start is greater than the end or if the end is greater than the len.
+fn to_range<R: RangeBounds<usize>>(range: R, len: usize) -> Range<usize> {
+    let start = match range.start_bound() {
+        Bound::Included(&start) => start,
+        Bound::Excluded(&start) => start.checked_add(1).expect("range start should not overflow"),
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(&end) => end.checked_add(1).expect("range end should not overflow"),
+        Bound::Excluded(&end) => end,
+        Bound::Unbounded => len,
+    };
+    if start > end {
+        panic!("range start {} should be less or equal the end {}", start, end)
+    }
+    if end > len {
+        panic!("range end {} should be less or equal the len {}", end, len)
+    }
+    start..end
+}
+
+/// The first node, the last node and the number of elements of a chain of linked nodes.
+type Chain<T, A> = (NonNull<Node<T, A>>, NonNull<Node<T, A>>, usize);
+
+struct Node<T, A: Allocator = Global> {
+    next: Option<NonNull<Node<T, A>>>,
+    prev: Option<NonNull<Node<T, A>>>,
+    data: NodeBuf<T, A>,
+    // the slot of the entry in the index, valid only while the index is not dirty
+    pos: usize,
+}
+
+impl<T, A: Allocator> Node<T, A> {
+    fn new_in(alloc: A) -> Self {
+        Node {
+            next: None,
+            prev: None,
+            data: NodeBuf::new_in(alloc),
+            pos: 0,
+        }
     }
-    #[inline]
-    unsafe fn split_and_push(&mut self, el: T) -> NonNull<Node<T>> {
-        let mut next_node = Box::leak(Box::new(Node::new())).into();
-        self.split(next_node);
-        next_node.as_mut().data.push(el);
-        next_node
-    }
-    #[inline]
-    unsafe fn split_and_insert(&mut self, el: T, idx: usize) -> NonNull<Node<T>> {
-        let mut next_node = Box::leak(Box::new(Node::new())).into();
-        self.split(next_node);
-        let data_len = self.data.len();
-        if idx > data_len {
-            next_node.as_mut().data.insert(idx - data_len, el);
-        } else {
-            self.data.insert(idx, el);
-        }
-        next_node
+
+    fn is_full(&self, cap: usize) -> bool {
+        self.data.len() >= cap
     }
 }
 
 
 #[cfg(test)]
 mod tests {
+    use std::string::ToString;
+    use std::vec;
+    use std::vec::Vec;
     use crate::UnrolledLinkedList;
+    use crate::error::ListError;
+    use crate::policy::RebalancePolicy;
+    use allocator_api2::alloc::{AllocError, Allocator, Global, Layout};
+    use std::cell::Cell;
+    use std::ptr::NonNull;
+
+    pub(crate) struct DropCounter<'a>(pub(crate) &'a Cell<usize>);
+
+    impl Drop for DropCounter<'_> {
+        fn drop(&mut self) {
+            self.0.set(self.0.get() + 1);
+        }
+    }
+
+    pub(crate) fn node_lens<T, A: Allocator + Clone>(list: &UnrolledLinkedList<T, A>) -> Vec<usize> {
+        let mut lens = vec![];
+        let mut next_node = list.head;
+        unsafe {
+            while let Some(n) = next_node {
+                lens.push(n.as_ref().data.len());
+                next_node = n.as_ref().next;
+            }
+        }
+        lens
+    }
 
     #[test]
     fn push_test() {
@@ -451,7 +1619,7 @@ mod tests {
         list.push(13);
 
         unsafe {
-            let vec = list.tail.unwrap().as_ref().data.clone();
+            let vec = list.tail.unwrap().as_ref().data.to_vec();
             assert_eq!(vec, vec![11, 12, 13]);
         }
     }
@@ -566,10 +1734,10 @@ mod tests {
     #[test]
     fn remove_loop_test() {
         let mut list = UnrolledLinkedList::new();
-        for el in (0..1000).into_iter() {
+        for el in 0..1000 {
             list.push(el);
         }
-        for _ in (0..1000).into_iter() {
+        for _ in 0..1000 {
             let _ = list.remove(0);
         }
         assert!(list.is_empty())
@@ -587,9 +1755,9 @@ mod tests {
         list.insert(0, 7);
 
         unsafe {
-            let vec = list.tail.unwrap().as_ref().data.clone();
+            let vec = list.tail.unwrap().as_ref().data.to_vec();
             assert_eq!(vec, vec![2, 1]);
-            let vec = list.head.unwrap().as_ref().data.clone();
+            let vec = list.head.unwrap().as_ref().data.to_vec();
             assert_eq!(vec, vec![7, 6, 5]);
         }
     }
@@ -622,7 +1790,7 @@ mod tests {
 
         unsafe {
             let (node, start_idx) = list.find_node(5);
-            let data = node.expect("").as_ref().data.clone();
+            let data = node.expect("").as_ref().data.to_vec();
             assert_eq!(data, vec![5, 6]);
             assert_eq!(5 - start_idx, 1);
         }
@@ -640,4 +1808,801 @@ mod tests {
         assert_eq!(list.get(3), Some(&4));
         assert_eq!(list.get_mut(4), Some(&mut 1));
     }
+
+    #[test]
+    fn drop_test() {
+        let drops = Cell::new(0);
+        let mut list = UnrolledLinkedList::with_capacity(4);
+        for _ in 0..100 {
+            list.push(DropCounter(&drops));
+        }
+        for _ in 0..10 {
+            list.insert(5, DropCounter(&drops));
+        }
+        drop(list);
+        assert_eq!(drops.get(), 110);
+    }
+
+    #[test]
+    fn clear_test() {
+        let drops = Cell::new(0);
+        let mut list = UnrolledLinkedList::with_capacity(4);
+        for _ in 0..50 {
+            list.push(DropCounter(&drops));
+        }
+        list.clear();
+        assert_eq!(drops.get(), 50);
+        assert!(list.is_empty());
+        assert!(list.head.is_none() && list.tail.is_none());
+
+        for _ in 0..10 {
+            list.push(DropCounter(&drops));
+        }
+        assert_eq!(list.len(), 10);
+        drop(list);
+        assert_eq!(drops.get(), 60);
+    }
+
+    #[test]
+    fn pop_remove_drop_test() {
+        let drops = Cell::new(0);
+        let mut list = UnrolledLinkedList::with_capacity(4);
+        for _ in 0..20 {
+            list.push(DropCounter(&drops));
+        }
+        for _ in 0..5 {
+            drop(list.pop());
+            drop(list.remove(3));
+        }
+        assert_eq!(drops.get(), 10);
+        drop(list);
+        assert_eq!(drops.get(), 20);
+    }
+
+    #[test]
+    fn no_empty_nodes_test() {
+        let mut list = UnrolledLinkedList::with_capacity(4);
+        for i in 0..9 {
+            list.push(i);
+        }
+        assert_eq!(list.remove(8), 8);
+        assert!(node_lens(&list).iter().all(|l| *l > 0));
+        while list.pop().is_some() {
+            assert!(node_lens(&list).iter().all(|l| *l > 0));
+        }
+        assert!(list.head.is_none() && list.tail.is_none());
+
+        list.push(1);
+        assert_eq!(list.remove(0), 1);
+        assert!(list.head.is_none() && list.tail.is_none());
+        assert_eq!(list.pop(), None);
+        assert_eq!(list.len(), 0);
+    }
+
+    #[test]
+    fn small_cap_test() {
+        for cap in 0..3 {
+            let mut list = UnrolledLinkedList::with_capacity(cap);
+            for i in 0..10 {
+                list.insert(i / 2, i);
+            }
+            assert!(node_lens(&list).iter().all(|l| *l > 0));
+            assert_eq!(node_lens(&list).iter().sum::<usize>(), 10);
+            let mut vec: Vec<_> = list.iter().cloned().collect();
+            vec.sort();
+            assert_eq!(vec, (0..10).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn insert_at_end_test() {
+        let mut list = UnrolledLinkedList::with_capacity(4);
+        for i in 0..6 {
+            list.push(i);
+        }
+        list.insert(6, 6);
+        list.insert(7, 7);
+        list.push(8);
+        assert_eq!(list.iter().cloned().collect::<Vec<_>>(), (0..9).collect::<Vec<_>>());
+    }
+
+    pub(crate) fn check_invariants<T, A: Allocator + Clone>(list: &UnrolledLinkedList<T, A>) {
+        let lens = node_lens(list);
+        assert!(lens.iter().all(|l| *l > 0 && *l <= list.cap.max(1)));
+        assert_eq!(lens.iter().sum::<usize>(), list.len());
+        assert_eq!(list.head.is_none(), list.tail.is_none());
+        unsafe {
+            let mut prev = None;
+            let mut next_node = list.head;
+            while let Some(n) = next_node {
+                assert_eq!(n.as_ref().prev, prev);
+                prev = Some(n);
+                next_node = n.as_ref().next;
+            }
+            assert_eq!(prev, list.tail);
+        }
+        if let Some(index) = list.index.as_ref() {
+            assert!(!index.is_dirty());
+            index.check(list.head);
+            // Miri checks a sample of the positions
+            let step = if cfg!(miri) { 1 + list.len() / 8 } else { 1 };
+            for idx in (0..=list.len()).step_by(step) {
+                assert_eq!(index.find(idx), list.find_node_linear(idx));
+            }
+        }
+    }
+
+    #[test]
+    fn mixed_ops_test() {
+        mixed_ops(false);
+        mixed_ops(true);
+    }
+
+    fn mixed_ops(indexed: bool) {
+        let mut list = UnrolledLinkedList::with_capacity(4);
+        list.set_indexed(indexed);
+        let mut vec = vec![];
+        let mut seed: usize = 17;
+        for i in 0..300 {
+            seed = seed.wrapping_mul(1103515245).wrapping_add(12345) % (1 << 31);
+            match seed % 5 {
+                0 | 1 => {
+                    let idx = seed % (vec.len() + 1);
+                    list.insert(idx, i);
+                    vec.insert(idx, i);
+                }
+                2 => {
+                    list.push(i);
+                    vec.push(i);
+                }
+                3 if !vec.is_empty() => {
+                    let idx = seed % vec.len();
+                    assert_eq!(list.remove(idx), vec.remove(idx));
+                }
+                _ => assert_eq!(list.pop(), vec.pop()),
+            }
+            check_invariants(&list);
+            assert_eq!(list.iter().collect::<Vec<_>>(), vec.iter().collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn deque_test() {
+        let mut list = UnrolledLinkedList::with_capacity(4);
+        assert_eq!(list.pop_front(), None);
+        assert_eq!(list.front(), None);
+        assert_eq!(list.back_mut(), None);
+        for i in 0..9 {
+            list.push_front(i);
+        }
+        check_invariants(&list);
+        assert_eq!(node_lens(&list), vec![1, 4, 4]);
+        assert_eq!(list.front(), Some(&8));
+        assert_eq!(list.back(), Some(&0));
+        *list.front_mut().unwrap() += 10;
+        *list.back_mut().unwrap() += 10;
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), vec![18, 7, 6, 5, 4, 3, 2, 1, 10]);
+
+        deque_ops(false);
+        deque_ops(true);
+    }
+
+    fn deque_ops(indexed: bool) {
+        let mut list = UnrolledLinkedList::with_capacity(4);
+        list.set_indexed(indexed);
+        let mut deque = std::collections::VecDeque::new();
+        let mut seed: usize = 23;
+        for i in 0..300 {
+            seed = seed.wrapping_mul(1103515245).wrapping_add(12345) % (1 << 31);
+            match seed % 4 {
+                0 => {
+                    list.push_front(i);
+                    deque.push_front(i);
+                }
+                1 => {
+                    list.push(i);
+                    deque.push_back(i);
+                }
+                2 => assert_eq!(list.pop_front(), deque.pop_front()),
+                _ => assert_eq!(list.pop(), deque.pop_back()),
+            }
+            check_invariants(&list);
+            assert_eq!(list.front(), deque.front());
+            assert_eq!(list.back(), deque.back());
+            assert_eq!(list.get(deque.len() / 2), deque.get(deque.len() / 2));
+            assert_eq!(list.iter().collect::<Vec<_>>(), deque.iter().collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn front_rebalance_test() {
+        for policy in [RebalancePolicy::BALANCED, RebalancePolicy::APPEND, RebalancePolicy::BTREE].iter() {
+            let mut list = UnrolledLinkedList::with_policy(8, *policy);
+            // the elements already in front stay in place unless the head node has no room in front of them
+            let mut moved = 0;
+            for el in 0..200 {
+                let front = list.front().map(|el| el as *const i32);
+                list.push_front(el);
+                if front.is_some() && front != list.get(1).map(|el| el as *const i32) {
+                    moved += 1;
+                }
+            }
+            check_invariants(&list);
+            let nodes = node_lens(&list).len();
+            assert!(moved <= 2 * nodes, "moved {} times for {} nodes", moved, nodes);
+
+            let min_len = policy.min_len(8);
+            for el in (0..200).rev() {
+                assert_eq!(list.pop_front(), Some(el));
+                check_invariants(&list);
+                if list.head != list.tail {
+                    assert!(node_lens(&list)[0] >= min_len, "{:?}", node_lens(&list));
+                }
+            }
+            assert!(list.is_empty());
+        }
+    }
+
+    #[test]
+    fn get_mut_test() {
+        let mut list = UnrolledLinkedList::with_capacity(4);
+        for i in 0..10 {
+            list.push(i);
+        }
+        for i in 0..10 {
+            *list.get_mut(i).unwrap() *= 2;
+        }
+        assert_eq!(list.get_mut(10), None);
+        assert_eq!(list.iter().cloned().collect::<Vec<_>>(), (0..10).map(|e| e * 2).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn send_sync_test() {
+        fn is_send<T: Send>(_: &T) {}
+        fn is_sync<T: Sync>(_: &T) {}
+
+        let mut list = UnrolledLinkedList::with_capacity(4);
+        for i in 0..10 {
+            list.push(i);
+        }
+        is_send(&list);
+        is_sync(&list);
+        is_send(&list.iter());
+        is_sync(&list.iter());
+        is_send(&list.iter_mut());
+        is_sync(&list.iter_mut());
+
+        let list = std::thread::spawn(move || {
+            list.push(10);
+            list
+        }).join().unwrap();
+        assert_eq!(list.iter().sum::<i32>(), 55);
+    }
+
+    fn hash_of<H: std::hash::Hash>(value: &H) -> u64 {
+        use std::hash::Hasher;
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        value.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    fn clone_test() {
+        let mut list = UnrolledLinkedList::with_capacity(4);
+        for i in 0..30 {
+            list.insert(i / 2, i.to_string());
+        }
+        let cloned = list.clone();
+        check_invariants(&cloned);
+        assert_eq!(node_lens(&cloned), node_lens(&list));
+        assert_eq!(cloned, list);
+
+        let mut target = UnrolledLinkedList::with_capacity(8);
+        for i in 0..100 {
+            target.push(i.to_string());
+        }
+        target.clone_from(&list);
+        check_invariants(&target);
+        assert_eq!(target, list);
+        assert_eq!(target.to_string(), list.to_string());
+
+        let mut target = UnrolledLinkedList::with_capacity(2);
+        target.set_indexed(true);
+        target.push("a".to_string());
+        target.clone_from(&list);
+        assert!(!target.is_indexed());
+        assert_eq!(target, list);
+
+        list.set_indexed(true);
+        let cloned = list.clone();
+        check_invariants(&cloned);
+        assert!(cloned.is_indexed());
+        assert_eq!(cloned.get(17), list.get(17));
+    }
+
+    #[test]
+    fn clone_drop_test() {
+        let value = std::rc::Rc::new(());
+        let mut list = UnrolledLinkedList::with_capacity(4);
+        for _ in 0..10 {
+            list.push(value.clone());
+        }
+        let mut target = UnrolledLinkedList::with_capacity(4);
+        for _ in 0..30 {
+            target.push(value.clone());
+        }
+        assert_eq!(std::rc::Rc::strong_count(&value), 41);
+        target.clone_from(&list);
+        check_invariants(&target);
+        assert_eq!(target.len(), 10);
+        assert_eq!(std::rc::Rc::strong_count(&value), 21);
+        drop(list);
+        target.clone_from(&UnrolledLinkedList::new());
+        assert!(target.is_empty());
+        assert_eq!(std::rc::Rc::strong_count(&value), 1);
+    }
+
+    #[test]
+    fn cmp_hash_test() {
+        let mut first = UnrolledLinkedList::with_capacity(3);
+        let mut second = UnrolledLinkedList::with_capacity(7);
+        for i in 0..20 {
+            first.push(i);
+            second.insert(0, 19 - i);
+        }
+        assert_ne!(node_lens(&first), node_lens(&second));
+        assert_eq!(first, second);
+        assert_eq!(first.cmp(&second), std::cmp::Ordering::Equal);
+        assert_eq!(hash_of(&first), hash_of(&second));
+
+        // the hashers writing the slices in bulk see the same writes for both layouts too
+        #[derive(Default)]
+        struct Writes(Vec<Vec<u8>>);
+        impl std::hash::Hasher for Writes {
+            fn finish(&self) -> u64 { 0 }
+            fn write(&mut self, bytes: &[u8]) { self.0.push(bytes.to_vec()) }
+        }
+        let (mut first_writes, mut second_writes) = (Writes::default(), Writes::default());
+        std::hash::Hash::hash(&first, &mut first_writes);
+        std::hash::Hash::hash(&second, &mut second_writes);
+        assert_eq!(first_writes.0, second_writes.0);
+
+        second.push(20);
+        assert_ne!(first, second);
+        assert!(first < second);
+        assert_ne!(hash_of(&first), hash_of(&second));
+        *second.get_mut(0).unwrap() = -1;
+        assert!(first > second);
+        assert_eq!(first.partial_cmp(&second), Some(std::cmp::Ordering::Greater));
+
+        let mut set = std::collections::HashSet::new();
+        set.insert(first.clone());
+        assert!(set.contains(&first));
+        let tree: std::collections::BTreeSet<_> = vec![first, second].into_iter().collect();
+        assert_eq!(tree.iter().next().unwrap().get(0), Some(&-1));
+    }
+
+    #[test]
+    fn from_iter_test() {
+        let list: UnrolledLinkedList<_> = (0..20).collect();
+        check_invariants(&list);
+        assert_eq!(node_lens(&list), vec![8, 8, 4]);
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), (0..20).collect::<Vec<_>>());
+
+        let list = UnrolledLinkedList::from_iter_with(8, 0.5, 0..20);
+        check_invariants(&list);
+        assert_eq!(node_lens(&list), vec![4, 4, 4, 4, 4]);
+
+        let list = UnrolledLinkedList::from_iter_with(8, 0.7, 0..20);
+        assert_eq!(node_lens(&list), vec![6, 6, 6, 2]);
+
+        let list = UnrolledLinkedList::from_iter_with(8, 0.01, 0..3);
+        assert_eq!(node_lens(&list), vec![1, 1, 1]);
+
+        let list = UnrolledLinkedList::<i32>::from_iter_with(8, 1.0, None);
+        check_invariants(&list);
+        assert!(list.is_empty());
+
+        assert_eq!(UnrolledLinkedList::from(vec![1, 2, 3]), UnrolledLinkedList::from([1, 2, 3]));
+    }
+
+    #[test]
+    #[should_panic]
+    fn from_iter_with_zero_test() {
+        UnrolledLinkedList::from_iter_with(8, 0.0, 0..3);
+    }
+
+    #[test]
+    fn extend_test() {
+        let mut list = UnrolledLinkedList::with_capacity(4);
+        list.push(0);
+        list.extend(1..10);
+        check_invariants(&list);
+        assert_eq!(node_lens(&list), vec![4, 4, 2]);
+
+        list.set_indexed(true);
+        list.extend(&[10, 11, 12]);
+        check_invariants(&list);
+        assert_eq!(node_lens(&list), vec![4, 4, 4, 1]);
+        assert_eq!(list.get(11), Some(&11));
+
+        list.extend(Vec::<i32>::new());
+        check_invariants(&list);
+        list.insert(2, 100);
+        list.remove(2);
+        assert_eq!(list, (0..13).collect());
+
+        let drops = Cell::new(0);
+        let mut list = UnrolledLinkedList::with_capacity(4);
+        list.extend((0..10).map(|_| DropCounter(&drops)));
+        drop(list);
+        assert_eq!(drops.get(), 10);
+    }
+
+    #[test]
+    fn split_off_test() {
+        for cap in 1..6 {
+            for at in 0..=20 {
+                let mut list = UnrolledLinkedList::from_iter_with(cap, 0.6, 0..20);
+                list.set_indexed(at % 2 == 0);
+                let back = list.split_off(at);
+                check_invariants(&list);
+                check_invariants(&back);
+                assert_eq!(back.is_indexed(), list.is_indexed());
+                assert_eq!(list, (0..at).collect());
+                assert_eq!(back, (at..20).collect());
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn split_off_out_of_bounds_test() {
+        UnrolledLinkedList::from(vec![1, 2]).split_off(3);
+    }
+
+    #[test]
+    fn insert_list_test() {
+        for index in 0..=10 {
+            for other_len in 0..12 {
+                let mut list = UnrolledLinkedList::from_iter_with(4, 0.5, 0..10);
+                list.set_indexed(other_len % 2 == 0);
+                let mut other = UnrolledLinkedList::from_iter_with(3, 1.0, 100..100 + other_len);
+                list.insert_list(index, &mut other);
+                check_invariants(&list);
+                check_invariants(&other);
+                assert!(other.is_empty());
+
+                let mut expected: Vec<_> = (0..10).collect();
+                expected.splice(index..index, 100..100 + other_len);
+                assert_eq!(list, expected.into_iter().collect());
+            }
+        }
+    }
+
+    #[test]
+    fn append_prepend_test() {
+        let mut list = UnrolledLinkedList::with_capacity(4);
+        let mut other: UnrolledLinkedList<_> = (0..5).collect();
+        list.append(&mut other);
+        check_invariants(&list);
+        assert_eq!(list, (0..5).collect());
+
+        let mut other = UnrolledLinkedList::from_iter_with(4, 0.25, 5..8);
+        list.append(&mut other);
+        check_invariants(&list);
+        assert_eq!(list, (0..8).collect());
+
+        let mut other: UnrolledLinkedList<_> = (-3..0).collect();
+        list.prepend(&mut other);
+        check_invariants(&list);
+        assert_eq!(list, (-3..8).collect());
+
+        let drops = Cell::new(0);
+        let mut list = UnrolledLinkedList::with_capacity(4);
+        for _ in 0..100 {
+            let mut other: UnrolledLinkedList<_> = (0..3).map(|_| DropCounter(&drops)).collect();
+            list.append(&mut other);
+            let mut other: UnrolledLinkedList<_> = (0..2).map(|_| DropCounter(&drops)).collect();
+            list.prepend(&mut other);
+        }
+        check_invariants(&list);
+        assert_eq!(list.len(), 500);
+        assert!(node_lens(&list).len() <= 250);
+        let back = list.split_off(123);
+        drop(list);
+        assert_eq!(drops.get(), 123);
+        drop(back);
+        assert_eq!(drops.get(), 500);
+    }
+
+    #[test]
+    fn append_capacity_test() {
+        let mut list: UnrolledLinkedList<_> = UnrolledLinkedList::with_capacity(2);
+        list.extend(0..3);
+        let mut other = UnrolledLinkedList::with_capacity(16);
+        other.extend(3..20);
+        list.append(&mut other);
+        check_invariants(&list);
+        assert!(other.is_empty());
+        assert_eq!(list, (0..20).collect());
+
+        let mut other = UnrolledLinkedList::with_capacity(5);
+        other.extend(-9..0);
+        list.prepend(&mut other);
+        check_invariants(&list);
+        assert_eq!(list, (-9..20).collect());
+
+        list.set_indexed(true);
+        let mut other = UnrolledLinkedList::from_iter_with(32, 0.5, 100..150);
+        list.insert_list(10, &mut other);
+        check_invariants(&list);
+        list.push(20);
+        list.insert(30, 200);
+        check_invariants(&list);
+        let mut expected: Vec<_> = (-9..21).collect();
+        expected.splice(10..10, 100..150);
+        expected.insert(30, 200);
+        assert_eq!(list, expected.into_iter().collect());
+    }
+
+    #[test]
+    fn many_appends_test() {
+        // walking the nodes on every append would take minutes here
+        let count = if cfg!(miri) { 200 } else { 100_000 };
+        let mut list = UnrolledLinkedList::with_capacity(2);
+        for i in 0..count {
+            let mut other = UnrolledLinkedList::with_capacity(2);
+            other.extend([2 * i, 2 * i + 1].iter().copied());
+            list.append(&mut other);
+            let mut other = UnrolledLinkedList::with_capacity(2);
+            other.extend([-2 * i - 2, -2 * i - 1].iter().copied());
+            list.prepend(&mut other);
+        }
+        check_invariants(&list);
+        assert_eq!(list.len(), 4 * count as usize);
+        assert_eq!(node_lens(&list).len(), 2 * count as usize);
+        assert!(list.iter().copied().eq(-2 * count..2 * count));
+    }
+
+    #[test]
+    fn rotate_test() {
+        for indexed in [false, true].iter().copied() {
+            let mut list = UnrolledLinkedList::with_capacity(4);
+            list.set_indexed(indexed);
+            list.extend(0..30);
+            let mut vec: Vec<_> = (0..30).collect();
+            for k in [0, 30, 1, 4, 7, 29, 13, 16].iter().copied() {
+                list.rotate_left(k);
+                vec.rotate_left(k);
+                check_invariants(&list);
+                assert_eq!(list.iter().collect::<Vec<_>>(), vec.iter().collect::<Vec<_>>());
+                list.rotate_right(k / 2);
+                vec.rotate_right(k / 2);
+                check_invariants(&list);
+                assert_eq!(list.iter().collect::<Vec<_>>(), vec.iter().collect::<Vec<_>>());
+            }
+            assert_eq!(list.get(17), vec.get(17));
+            assert!(node_lens(&list).len() <= 30 / 2 + 1);
+        }
+
+        let mut empty = UnrolledLinkedList::<i32>::new();
+        empty.rotate_left(0);
+        empty.rotate_right(0);
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "mid 4 should be less or equal the len 3")]
+    fn rotate_out_of_bounds_test() {
+        let mut list: UnrolledLinkedList<_> = (0..3).collect();
+        list.rotate_left(4);
+    }
+
+    #[test]
+    fn retain_test() {
+        for cap in 1..7 {
+            for modulo in 1..5 {
+                let mut list = UnrolledLinkedList::from_iter_with(cap, 0.5, 0..40);
+                list.set_indexed(modulo % 2 == 0);
+                list.retain(|el| el % modulo == 0);
+                check_invariants(&list);
+                assert_eq!(list, (0..40).filter(|el| el % modulo == 0).collect());
+                let lens = node_lens(&list);
+                assert!(lens[..lens.len() - 1].iter().all(|&len| len >= cap / 2));
+            }
+        }
+        let mut list: UnrolledLinkedList<_> = (0..40).collect();
+        list.retain(|_| false);
+        check_invariants(&list);
+        assert!(list.is_empty());
+    }
+
+    #[test]
+    fn retain_panic_test() {
+        let drops = Cell::new(0);
+        let mut list = UnrolledLinkedList::with_capacity(4);
+        for _ in 0..20 {
+            list.push(DropCounter(&drops));
+        }
+        let mut calls = 0;
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            list.retain(|_| {
+                calls += 1;
+                if calls == 10 {
+                    panic!("predicate")
+                }
+                calls % 2 == 0
+            })
+        }));
+        assert!(result.is_err());
+        check_invariants(&list);
+        assert_eq!(drops.get(), 5);
+        assert_eq!(list.len(), 15);
+        drop(list);
+        assert_eq!(drops.get(), 20);
+    }
+
+    #[test]
+    fn try_ops_test() {
+        let mut list = UnrolledLinkedList::with_capacity(4);
+        assert_eq!(list.try_remove(0), Err(ListError::IndexOutOfBounds { index: 0, len: 0 }));
+        assert_eq!(list.try_insert(1, 0), Err(ListError::IndexOutOfBounds { index: 1, len: 0 }));
+        for i in 0..20 {
+            assert_eq!(list.try_insert(i / 2, i), Ok(()));
+            check_invariants(&list);
+        }
+        assert_eq!(list.spare_len, 0);
+        let mut expected: Vec<_> = vec![];
+        for i in 0..20 {
+            expected.insert(i / 2, i);
+        }
+        assert_eq!(list, expected.iter().copied().collect());
+
+        assert_eq!(list.try_remove(20), Err(ListError::IndexOutOfBounds { index: 20, len: 20 }));
+        assert_eq!(list.try_remove(3), Ok(expected.remove(3)));
+        assert!(list.try_split_off(20).is_err());
+        let back = list.try_split_off(9).unwrap();
+        check_invariants(&list);
+        check_invariants(&back);
+        assert_eq!(list.iter().chain(back.iter()).copied().collect::<Vec<_>>(), expected);
+        assert_eq!(ListError::IndexOutOfBounds { index: 1, len: 0 }.to_string(), "index 1 is out of bounds of the list with len 0");
+    }
+
+    #[test]
+    fn try_reserve_test() {
+        for policy in [RebalancePolicy::BALANCED, RebalancePolicy::APPEND, RebalancePolicy::BTREE].iter() {
+            for start in 0..10 {
+                let mut list = UnrolledLinkedList::with_policy(8, *policy);
+                list.extend(0..start);
+                list.try_reserve(100).unwrap();
+                let reserved = list.spare_len;
+                for el in 0..100 {
+                    list.push(el);
+                }
+                check_invariants(&list);
+                assert!(list.spare_len <= reserved);
+                let mut fresh = 0;
+                let mut next_node = list.head;
+                while let Some(node) = next_node {
+                    unsafe {
+                        fresh += (node.as_ref().data.capacity() < 8) as usize;
+                        next_node = node.as_ref().next;
+                    }
+                }
+                assert_eq!(fresh, 0);
+            }
+        }
+        let mut list = UnrolledLinkedList::<i32>::new();
+        list.try_reserve(0).unwrap();
+        assert_eq!(list.spare_len, 0);
+        list.try_reserve(17).unwrap();
+        assert_eq!(list.spare_len, 5);
+        drop(list);
+    }
+
+    #[test]
+    fn alloc_error_test() {
+        let mut list = UnrolledLinkedList::<u64>::with_capacity(usize::MAX / 4);
+        assert_eq!(list.try_reserve(1), Err(ListError::AllocError));
+        assert_eq!(list.try_insert(0, 1), Err(ListError::AllocError));
+        assert!(list.is_empty());
+        assert_eq!(ListError::AllocError.to_string(), "memory allocation failed");
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn std_error_test() {
+        let error: &dyn std::error::Error = &ListError::AllocError;
+        assert_eq!(error.to_string(), "memory allocation failed");
+    }
+
+    /// Counts the live allocations, failing them once the limit is reached.
+    #[derive(Clone)]
+    struct CountingAlloc<'a> {
+        live: &'a Cell<usize>,
+        limit: usize,
+    }
+
+    unsafe impl Allocator for CountingAlloc<'_> {
+        fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+            if self.live.get() >= self.limit {
+                return Err(AllocError);
+            }
+            self.live.set(self.live.get() + 1);
+            Global.allocate(layout)
+        }
+
+        unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+            self.live.set(self.live.get() - 1);
+            Global.deallocate(ptr, layout)
+        }
+    }
+
+    #[test]
+    fn allocator_test() {
+        let live = Cell::new(0);
+        let alloc = CountingAlloc { live: &live, limit: usize::MAX };
+        {
+            let mut list = UnrolledLinkedList::with_capacity_in(4, alloc);
+            list.extend(0..20);
+            list.insert(3, 100);
+            check_invariants(&list);
+            // every node holds a header and a buffer
+            assert_eq!(live.get(), 2 * node_lens(&list).len());
+
+            let back = list.split_off(10);
+            let copy = list.clone();
+            assert_eq!(copy, list);
+            assert_eq!(back.iter().copied().collect::<Vec<_>>(), (9..20).collect::<Vec<_>>());
+            let drained: Vec<_> = list.drain(2..6).collect();
+            assert_eq!(drained, vec![2, 100, 3, 4]);
+            let mut iter = back.into_iter();
+            assert_eq!(iter.next(), Some(9));
+            assert_eq!(iter.next_back(), Some(19));
+            assert!(live.get() > 0);
+        }
+        assert_eq!(live.get(), 0);
+
+        let alloc = CountingAlloc { live: &live, limit: 4 };
+        let mut list = UnrolledLinkedList::with_capacity_in(2, alloc);
+        assert_eq!(list.try_reserve(2), Ok(()));
+        assert_eq!(live.get(), 4);
+        list.extend(0..3);
+        list.push(3);
+        assert_eq!(live.get(), 4);
+        assert_eq!(list.try_insert(4, 4), Err(ListError::AllocError));
+        assert_eq!(list.len(), 4);
+        drop(list);
+        assert_eq!(live.get(), 0);
+    }
+
+    #[test]
+    fn indexed_allocator_test() {
+        let live = Cell::new(0);
+        let alloc = CountingAlloc { live: &live, limit: usize::MAX };
+        let mut list = UnrolledLinkedList::with_capacity_in(2, alloc);
+        list.set_indexed(true);
+        list.extend(0..8);
+        assert_eq!(node_lens(&list), vec![2, 2, 2, 2]);
+        // the entries of the index take one more buffer
+        assert_eq!(live.get(), 2 * 4 + 1);
+        drop(list);
+        assert_eq!(live.get(), 0);
+
+        // the buffer of the index is full, so only the node fits in the limit
+        let alloc = CountingAlloc { live: &live, limit: 2 * 4 + 1 + 2 };
+        let mut list = UnrolledLinkedList::with_capacity_in(2, alloc);
+        list.set_indexed(true);
+        list.extend(0..8);
+        assert_eq!(list.try_insert(1, 100), Err(ListError::AllocError));
+        assert_eq!(list.try_reserve(1), Err(ListError::AllocError));
+        assert_eq!(list.try_split_off(4).map(|back| back.len()), Err(ListError::AllocError));
+        check_invariants(&list);
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), (0..8).collect::<Vec<_>>());
+        assert_eq!(list.try_split_off(0).map(|back| back.len()), Ok(8));
+        drop(list);
+        assert_eq!(live.get(), 0);
+    }
 }
+
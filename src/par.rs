@@ -0,0 +1,214 @@
+//! The parallel iterators and the parallel construction with [rayon](https://docs.rs/rayon).
+//!
+//! The work is split along the node boundaries: every node buffer is processed by one thread as a whole.
+//! The parallel construction builds a chain of nodes per thread and links the chains at the end.
+//!
+//! # Example
+//! ```rust
+//! use rayon::prelude::*;
+//! use unrolled_linked_list::UnrolledLinkedList;
+//!
+//! let mut list: UnrolledLinkedList<u64> = (0..10_000).into_par_iter().collect();
+//! list.par_iter_mut().for_each(|el| *el *= 2);
+//! assert_eq!(list.par_iter().sum::<u64>(), 99_990_000);
+//! ```
+
+use core::fmt;
+use alloc::vec::Vec;
+use rayon::iter::plumbing::UnindexedConsumer;
+use rayon::iter::{FromParallelIterator, IntoParallelIterator, ParallelExtend, ParallelIterator};
+use allocator_api2::alloc::{Allocator, Global};
+use allocator_api2::boxed::Box;
+use crate::UnrolledLinkedList;
+
+/// A parallel iterator over the elements of a `UnrolledLinkedList`.
+///
+/// This `struct` is created by the `into_par_iter` method on `&UnrolledLinkedList`
+/// (provided by the `IntoParallelIterator` trait).
+pub struct ParIter<'a, T> {
+    chunks: Vec<&'a [T]>,
+}
+
+impl<T> fmt::Debug for ParIter<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ParIter").field(&self.chunks.len()).finish()
+    }
+}
+
+impl<'a, T: Sync> ParallelIterator for ParIter<'a, T> {
+    type Item = &'a T;
+
+    fn drive_unindexed<C: UnindexedConsumer<Self::Item>>(self, consumer: C) -> C::Result {
+        self.chunks.into_par_iter().flat_map_iter(|chunk| chunk.iter()).drive_unindexed(consumer)
+    }
+}
+
+impl<'a, T: Sync, A: Allocator + Clone> IntoParallelIterator for &'a UnrolledLinkedList<T, A> {
+    type Iter = ParIter<'a, T>;
+    type Item = &'a T;
+
+    fn into_par_iter(self) -> Self::Iter {
+        ParIter { chunks: self.chunks().collect() }
+    }
+}
+
+/// A mutable parallel iterator over the elements of a `UnrolledLinkedList`.
+///
+/// This `struct` is created by the `into_par_iter` method on `&mut UnrolledLinkedList`
+/// (provided by the `IntoParallelIterator` trait).
+pub struct ParIterMut<'a, T> {
+    chunks: Vec<&'a mut [T]>,
+}
+
+impl<T> fmt::Debug for ParIterMut<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ParIterMut").field(&self.chunks.len()).finish()
+    }
+}
+
+impl<'a, T: Send> ParallelIterator for ParIterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn drive_unindexed<C: UnindexedConsumer<Self::Item>>(self, consumer: C) -> C::Result {
+        self.chunks.into_par_iter().flat_map_iter(|chunk| chunk.iter_mut()).drive_unindexed(consumer)
+    }
+}
+
+impl<'a, T: Send, A: Allocator + Clone> IntoParallelIterator for &'a mut UnrolledLinkedList<T, A> {
+    type Iter = ParIterMut<'a, T>;
+    type Item = &'a mut T;
+
+    fn into_par_iter(self) -> Self::Iter {
+        ParIterMut { chunks: self.chunks_mut().collect() }
+    }
+}
+
+/// An owning parallel iterator over the elements of a `UnrolledLinkedList`.
+///
+/// This `struct` is created by the `into_par_iter` method on `UnrolledLinkedList`
+/// (provided by the `IntoParallelIterator` trait).
+/// The node headers are freed right away and the node buffers are handed to the threads.
+pub struct IntoParIter<T, A: Allocator = Global> {
+    buffers: Vec<allocator_api2::vec::Vec<T, A>>,
+}
+
+impl<T, A: Allocator> fmt::Debug for IntoParIter<T, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("IntoParIter").field(&self.buffers.len()).finish()
+    }
+}
+
+impl<T: Send, A: Allocator + Send> ParallelIterator for IntoParIter<T, A> {
+    type Item = T;
+
+    fn drive_unindexed<C: UnindexedConsumer<Self::Item>>(self, consumer: C) -> C::Result {
+        self.buffers.into_par_iter().flat_map_iter(|buffer| buffer).drive_unindexed(consumer)
+    }
+}
+
+impl<T: Send, A: Allocator + Clone + Send> IntoParallelIterator for UnrolledLinkedList<T, A> {
+    type Iter = IntoParIter<T, A>;
+    type Item = T;
+
+    fn into_par_iter(mut self) -> Self::Iter {
+        self.set_indexed(false);
+        let mut buffers = Vec::new();
+        while let Some(node) = self.pop_front_node() {
+            buffers.push(Box::into_inner(node).data.into_vec());
+        }
+        IntoParIter { buffers }
+    }
+}
+
+impl<T: Send, A: Allocator + Clone + Send + Sync> ParallelExtend<T> for UnrolledLinkedList<T, A> {
+    /// Packs the elements into a chain of nodes per thread and links the chains to the end of the list.
+    fn par_extend<I: IntoParallelIterator<Item=T>>(&mut self, par_iter: I) {
+        let (cap, policy, alloc) = (self.cap, self.policy, self.alloc.clone());
+        let empty = move || UnrolledLinkedList::with_policy_in(cap, policy, alloc.clone());
+        let mut chain = par_iter
+            .into_par_iter()
+            .fold(&empty, |mut list, el| {
+                list.extend_packed(Some(el), cap);
+                list
+            })
+            .reduce(&empty, |mut front, mut back| {
+                front.append(&mut back);
+                front
+            });
+        self.append(&mut chain);
+    }
+}
+
+impl<'a, T: Copy + Send + Sync + 'a, A: Allocator + Clone + Send + Sync> ParallelExtend<&'a T> for UnrolledLinkedList<T, A> {
+    fn par_extend<I: IntoParallelIterator<Item=&'a T>>(&mut self, par_iter: I) {
+        self.par_extend(par_iter.into_par_iter().copied())
+    }
+}
+
+impl<T: Send> FromParallelIterator<T> for UnrolledLinkedList<T> {
+    fn from_par_iter<I: IntoParallelIterator<Item=T>>(par_iter: I) -> Self {
+        let mut list = UnrolledLinkedList::new();
+        list.par_extend(par_iter);
+        list
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::string::{String, ToString};
+    use std::vec::Vec;
+    use rayon::prelude::*;
+    use crate::UnrolledLinkedList;
+    use crate::policy::RebalancePolicy;
+    use crate::tests::{check_invariants, node_lens};
+
+    // Miri runs shorter lists
+    const LEN: u32 = if cfg!(miri) { 500 } else { 10_000 };
+
+    #[test]
+    fn par_iter_test() {
+        let mut list = UnrolledLinkedList::with_capacity(16);
+        list.extend(0..LEN as u64);
+        assert_eq!(list.par_iter().sum::<u64>(), (0..LEN as u64).sum::<u64>());
+        assert_eq!(list.par_iter().copied().collect::<Vec<_>>(), (0..LEN as u64).collect::<Vec<_>>());
+
+        list.par_iter_mut().for_each(|el| *el += 1);
+        check_invariants(&list);
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), (1..LEN as u64 + 1).collect::<Vec<_>>());
+
+        let empty = UnrolledLinkedList::<u64>::new();
+        assert_eq!(empty.par_iter().count(), 0);
+    }
+
+    #[test]
+    fn into_par_iter_test() {
+        let list: UnrolledLinkedList<_> = (0..LEN / 2).map(|el| el.to_string()).collect();
+        let strings: Vec<String> = list.into_par_iter().collect();
+        assert_eq!(strings, (0..LEN / 2).map(|el| el.to_string()).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn from_par_iter_test() {
+        let list: UnrolledLinkedList<u32> = (0..LEN).into_par_iter().collect();
+        check_invariants(&list);
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), (0..LEN).collect::<Vec<_>>());
+
+        let list: UnrolledLinkedList<u32> = (0..LEN / 10).into_par_iter().filter(|el| el % 3 == 0).collect();
+        check_invariants(&list);
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), (0..LEN / 10).filter(|el| el % 3 == 0).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn par_extend_test() {
+        let mut list = UnrolledLinkedList::with_policy(8, RebalancePolicy::APPEND);
+        list.set_indexed(true);
+        list.extend(0..3);
+        list.par_extend(3..LEN / 2);
+        list.par_extend(&[LEN / 2, LEN / 2 + 1]);
+        check_invariants(&list);
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), (0..LEN / 2 + 2).collect::<Vec<_>>());
+        assert_eq!(list.policy(), RebalancePolicy::APPEND);
+        assert!(list.is_indexed());
+        assert!(node_lens(&list).iter().all(|len| *len <= 8));
+    }
+}
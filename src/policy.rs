@@ -0,0 +1,191 @@
+//! The rules the list follows when it splits and merges its nodes.
+//!
+//! The policy is chosen at construction with [`UnrolledLinkedList::with_policy`](crate::UnrolledLinkedList::with_policy)
+//! and is kept for the lifetime of the list.
+//!
+//! # Example
+//! ```rust
+//! use unrolled_linked_list::UnrolledLinkedList;
+//! use unrolled_linked_list::policy::RebalancePolicy;
+//!
+//! let mut log = UnrolledLinkedList::with_policy(16, RebalancePolicy::APPEND);
+//! for el in 0..100 {
+//!     log.push(el);
+//! }
+//! assert_eq!(log.policy(), RebalancePolicy::APPEND);
+//! ```
+
+/// The configuration of splitting and merging the nodes.
+///
+/// Both fractions are taken from the node capacity and rounded down.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RebalancePolicy {
+    /// The fill, as a fraction of the capacity, below which a node is refilled from or merged with the neighbours.
+    pub min_fill: f64,
+    /// The fraction of the elements staying in a full node when it is split,
+    /// the rest is moved to a new node linked right after it.
+    pub split_point: f64,
+    /// Whether an under-filled node is merged into the previous node when all elements fit there.
+    pub merge_with_prev: bool,
+    /// Whether an under-filled node is refilled from the next node, or merged with it when all elements fit.
+    pub merge_with_next: bool,
+}
+
+impl RebalancePolicy {
+    /// The default policy: the full nodes are split in half and the nodes less than half full are refilled
+    /// from the next node. Leaves the room for the inserts in the middle of the list.
+    pub const BALANCED: RebalancePolicy = RebalancePolicy {
+        min_fill: 0.5,
+        split_point: 0.5,
+        merge_with_prev: false,
+        merge_with_next: true,
+    };
+
+    /// The policy for the lists growing at the end: the full nodes keep all their elements
+    /// and the new elements go to a new node, so the pushed nodes stay packed full.
+    pub const APPEND: RebalancePolicy = RebalancePolicy {
+        min_fill: 0.5,
+        split_point: 1.0,
+        merge_with_prev: true,
+        merge_with_next: true,
+    };
+
+    /// The policy similar to B-trees: the nodes are kept at least 2/3 full
+    /// and a full node keeps 2/3 of the elements when it is split.
+    pub const BTREE: RebalancePolicy = RebalancePolicy {
+        min_fill: 2.0 / 3.0,
+        split_point: 2.0 / 3.0,
+        merge_with_prev: true,
+        merge_with_next: true,
+    };
+
+    /// The number of elements below which a node with the given capacity is under-filled.
+    #[inline]
+    pub(crate) fn min_len(&self, cap: usize) -> usize {
+        (cap as f64 * self.min_fill) as usize
+    }
+
+    /// The number of elements staying in a node with the given length when it is split, at least one.
+    #[inline]
+    pub(crate) fn split_len(&self, len: usize) -> usize {
+        ((len as f64 * self.split_point) as usize).max(1).min(len)
+    }
+
+    pub(crate) fn validate(&self) {
+        if !(0.0..=1.0).contains(&self.min_fill) {
+            panic!("min fill {} should be in [0.0, 1.0]", self.min_fill)
+        }
+        if !(0.0..=1.0).contains(&self.split_point) {
+            panic!("split point {} should be in [0.0, 1.0]", self.split_point)
+        }
+    }
+}
+
+impl Default for RebalancePolicy {
+    fn default() -> Self {
+        RebalancePolicy::BALANCED
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::vec;
+    use std::vec::Vec;
+    use crate::UnrolledLinkedList;
+    use crate::policy::RebalancePolicy;
+    use crate::tests::{check_invariants, node_lens};
+
+    const POLICIES: [RebalancePolicy; 3] = [RebalancePolicy::BALANCED, RebalancePolicy::APPEND, RebalancePolicy::BTREE];
+
+    #[test]
+    fn push_test() {
+        let mut list = UnrolledLinkedList::with_policy(6, RebalancePolicy::BALANCED);
+        for el in 0..20 {
+            list.push(el);
+        }
+        assert_eq!(node_lens(&list), vec![3, 3, 3, 3, 3, 5]);
+
+        let mut list = UnrolledLinkedList::with_policy(6, RebalancePolicy::APPEND);
+        for el in 0..20 {
+            list.push(el);
+        }
+        check_invariants(&list);
+        assert_eq!(node_lens(&list), vec![6, 6, 6, 2]);
+
+        let mut list = UnrolledLinkedList::with_policy(6, RebalancePolicy::BTREE);
+        for el in 0..20 {
+            list.push(el);
+        }
+        check_invariants(&list);
+        assert_eq!(node_lens(&list), vec![4, 4, 4, 4, 4]);
+    }
+
+    #[test]
+    fn remove_test() {
+        let mut list = UnrolledLinkedList::with_policy(6, RebalancePolicy::BTREE);
+        list.extend(0..24);
+        assert_eq!(node_lens(&list), vec![6, 6, 6, 6]);
+        list.remove(6);
+        list.remove(6);
+        list.remove(6);
+        check_invariants(&list);
+        assert_eq!(node_lens(&list), vec![6, 4, 5, 6]);
+
+        let mut list = UnrolledLinkedList::with_policy(6, RebalancePolicy::APPEND);
+        list.extend(0..24);
+        for _ in 0..4 {
+            list.remove(18);
+        }
+        check_invariants(&list);
+        assert_eq!(node_lens(&list), vec![6, 6, 6, 2]);
+        list.remove(18);
+        check_invariants(&list);
+        assert_eq!(node_lens(&list), vec![6, 6, 6, 1]);
+
+        let mut list = UnrolledLinkedList::with_policy(6, RebalancePolicy::APPEND);
+        list.extend(0..9);
+        list.remove(0);
+        list.remove(0);
+        list.remove(0);
+        list.remove(0);
+        check_invariants(&list);
+        assert_eq!(node_lens(&list), vec![5]);
+    }
+
+    #[test]
+    fn mixed_ops_test() {
+        for policy in POLICIES.iter() {
+            for cap in 1..7 {
+                let mut list = UnrolledLinkedList::with_policy(cap, *policy);
+                let mut vec = vec![];
+                let mut seed = 7u32;
+                // Miri runs fewer operations
+                for i in 0..if cfg!(miri) { 100 } else { 400 } {
+                    seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+                    let r = (seed >> 16) as usize;
+                    if r % 5 < 3 || vec.is_empty() {
+                        let idx = r % (vec.len() + 1);
+                        list.insert(idx, i);
+                        vec.insert(idx, i);
+                    } else {
+                        let idx = r % vec.len();
+                        assert_eq!(list.remove(idx), vec.remove(idx));
+                    }
+                    check_invariants(&list);
+                }
+                assert_eq!(list.iter().copied().collect::<Vec<_>>(), vec);
+                let mut cursor = list.cursor_at_mut(vec.len() / 2);
+                while cursor.remove_current().is_some() {}
+                check_invariants(&list);
+                assert_eq!(list.len(), vec.len() / 2);
+                assert_eq!(list.policy(), *policy);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn invalid_policy_test() {
+        UnrolledLinkedList::<i32>::with_policy(8, RebalancePolicy { min_fill: 1.5, ..RebalancePolicy::BALANCED });
+    }
+}
@@ -0,0 +1,296 @@
+//! The serialization with [serde](https://serde.rs), available with the `serde` feature.
+//!
+//! The list is serialized as a sequence of elements, the same way as `Vec`.
+//! The deserialization streams the elements straight into the nodes of a list with the default capacity,
+//! filling every node up.
+//!
+//! The node capacity is not a part of the sequence. To keep it, use [`with_cap`] on the field:
+//! ```rust
+//! use serde::{Deserialize, Serialize};
+//! use unrolled_linked_list::UnrolledLinkedList;
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Doc {
+//!     #[serde(with = "unrolled_linked_list::ser::with_cap")]
+//!     lines: UnrolledLinkedList<String>,
+//! }
+//!
+//! let doc = Doc { lines: UnrolledLinkedList::with_capacity(32) };
+//! let json = serde_json::to_string(&doc).unwrap();
+//! assert_eq!(json, r#"{"lines":{"cap":32,"elements":[]}}"#);
+//! ```
+//!
+//! The capacity comes from the input, so it is accepted only up to [`MAX_CAP`](with_cap::MAX_CAP)
+//! and the node buffers are not reserved up front but grow with the elements.
+//! A list with a capacity that would not be accepted fails to serialize, so a round trip never loses it.
+
+use core::fmt;
+use core::iter;
+use core::marker::PhantomData;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde::de::{Error, MapAccess, SeqAccess, Unexpected, Visitor};
+use serde::ser::SerializeStruct;
+use allocator_api2::alloc::Allocator;
+use crate::UnrolledLinkedList;
+
+impl<T: Serialize, A: Allocator + Clone> Serialize for UnrolledLinkedList<T, A> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self)
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for UnrolledLinkedList<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_seq(ListVisitor { cap: None, marker: PhantomData })
+    }
+}
+
+/// Reads the elements of a sequence into a list with the given capacity or the default one.
+struct ListVisitor<T> {
+    cap: Option<usize>,
+    marker: PhantomData<T>,
+}
+
+impl<'de, T: Deserialize<'de>> Visitor<'de> for ListVisitor<T> {
+    type Value = UnrolledLinkedList<T>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a sequence")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut list = match self.cap {
+            Some(cap) => UnrolledLinkedList::with_capacity(cap),
+            None => UnrolledLinkedList::new(),
+        };
+        let mut error = None;
+        list.extend(iter::from_fn(|| match seq.next_element() {
+            Ok(el) => el,
+            Err(e) => {
+                error = Some(e);
+                None
+            }
+        }));
+        match error {
+            Some(e) => Err(e),
+            None => Ok(list),
+        }
+    }
+}
+
+/// The representation keeping the node capacity, for `#[serde(with = "unrolled_linked_list::ser::with_cap")]`.
+///
+/// The list is serialized as a struct with the fields `cap` and `elements`,
+/// so a round trip rebuilds the list with the same capacity.
+pub mod with_cap {
+    use super::*;
+
+    const FIELDS: &[&str] = &["cap", "elements"];
+
+    /// The largest node capacity accepted by [`deserialize`].
+    pub const MAX_CAP: usize = 1 << 16;
+
+    /// Whether the capacity is accepted by [`deserialize`].
+    fn is_valid_cap(cap: usize) -> bool {
+        cap > 0 && cap <= MAX_CAP
+    }
+
+    /// Rejects the capacity that is zero or above [`MAX_CAP`].
+    fn check_cap<E: Error>(cap: usize) -> Result<usize, E> {
+        if !is_valid_cap(cap) {
+            return Err(E::invalid_value(Unexpected::Unsigned(cap as u64), &"a node capacity in 1..=65536"));
+        }
+        Ok(cap)
+    }
+
+    /// Serializes the list as a struct with the node capacity and the sequence of elements.
+    ///
+    /// Fails if the capacity is zero or above [`MAX_CAP`], since [`deserialize`] would reject it.
+    pub fn serialize<T, A, S>(list: &UnrolledLinkedList<T, A>, serializer: S) -> Result<S::Ok, S::Error>
+        where T: Serialize, A: Allocator + Clone, S: Serializer {
+        if !is_valid_cap(list.cap) {
+            return Err(serde::ser::Error::custom(format_args!(
+                "the node capacity {} should be in 1..=65536 to be deserialized", list.cap)));
+        }
+        let mut state = serializer.serialize_struct("UnrolledLinkedList", 2)?;
+        state.serialize_field("cap", &list.cap)?;
+        state.serialize_field("elements", list)?;
+        state.end()
+    }
+
+    /// Deserializes the list written by [`serialize`], keeping its node capacity.
+    ///
+    /// The fields can come in any order. The capacity is checked before any element is read if it comes first,
+    /// otherwise the elements are repacked to the capacity.
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<UnrolledLinkedList<T>, D::Error>
+        where T: Deserialize<'de>, D: Deserializer<'de> {
+        deserializer.deserialize_struct("UnrolledLinkedList", FIELDS, CapVisitor(PhantomData))
+    }
+
+    struct CapVisitor<T>(PhantomData<T>);
+
+    /// Reads the elements with the capacity known beforehand.
+    struct Elements<T> {
+        cap: Option<usize>,
+        marker: PhantomData<T>,
+    }
+
+    impl<'de, T: Deserialize<'de>> serde::de::DeserializeSeed<'de> for Elements<T> {
+        type Value = UnrolledLinkedList<T>;
+
+        fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+            deserializer.deserialize_seq(ListVisitor { cap: self.cap, marker: PhantomData })
+        }
+    }
+
+    #[derive(Deserialize)]
+    #[serde(field_identifier, rename_all = "lowercase")]
+    enum Field { Cap, Elements }
+
+    impl<'de, T: Deserialize<'de>> Visitor<'de> for CapVisitor<T> {
+        type Value = UnrolledLinkedList<T>;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("struct UnrolledLinkedList")
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+            let cap = check_cap(seq.next_element()?.ok_or_else(|| Error::invalid_length(0, &self))?)?;
+            seq.next_element_seed(Elements { cap: Some(cap), marker: PhantomData })?
+                .ok_or_else(|| Error::invalid_length(1, &self))
+        }
+
+        fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+            let mut cap = None;
+            let mut elements: Option<UnrolledLinkedList<T>> = None;
+            while let Some(key) = map.next_key()? {
+                match key {
+                    Field::Cap => {
+                        if cap.is_some() {
+                            return Err(Error::duplicate_field("cap"));
+                        }
+                        cap = Some(check_cap(map.next_value()?)?);
+                    }
+                    Field::Elements => {
+                        if elements.is_some() {
+                            return Err(Error::duplicate_field("elements"));
+                        }
+                        elements = Some(map.next_value_seed(Elements { cap, marker: PhantomData })?);
+                    }
+                }
+            }
+            let cap = cap.ok_or_else(|| Error::missing_field("cap"))?;
+            let elements = elements.ok_or_else(|| Error::missing_field("elements"))?;
+            // the elements came before the capacity and need to be repacked
+            if elements.cap != cap {
+                return Ok(UnrolledLinkedList::from_iter_with(cap, 1.0, elements));
+            }
+            Ok(elements)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::format;
+    use std::string::{String, ToString};
+    use std::vec;
+    use std::vec::Vec;
+    use serde_test::{assert_tokens, assert_de_tokens, Token};
+    use crate::UnrolledLinkedList;
+    use crate::tests::{check_invariants, node_lens};
+
+    #[test]
+    fn seq_test() {
+        let mut list = UnrolledLinkedList::with_capacity(2);
+        list.push(1);
+        list.push(2);
+        list.push(3);
+        assert_tokens(&list, &[
+            Token::Seq { len: Some(3) },
+            Token::I32(1),
+            Token::I32(2),
+            Token::I32(3),
+            Token::SeqEnd,
+        ]);
+        assert_de_tokens(&UnrolledLinkedList::<i32>::new(), &[Token::Seq { len: Some(0) }, Token::SeqEnd]);
+    }
+
+    #[test]
+    fn json_test() {
+        let list: UnrolledLinkedList<_> = (0..20).map(|i| i.to_string()).collect();
+        let json = serde_json::to_string(&list).unwrap();
+        assert_eq!(json, serde_json::to_string(&list.iter().collect::<Vec<_>>()).unwrap());
+
+        let restored: UnrolledLinkedList<String> = serde_json::from_str(&json).unwrap();
+        check_invariants(&restored);
+        assert_eq!(restored, list);
+        assert_eq!(node_lens(&restored), vec![8, 8, 4]);
+
+        assert!(serde_json::from_str::<UnrolledLinkedList<u8>>("[1, 2, 300]").is_err());
+        assert!(serde_json::from_str::<UnrolledLinkedList<u8>>("{}").is_err());
+    }
+
+    #[test]
+    fn with_cap_test() {
+        let list = UnrolledLinkedList::from_iter_with(4, 0.5, 0..10);
+        let mut json = vec![];
+        super::with_cap::serialize(&list, &mut serde_json::Serializer::new(&mut json)).unwrap();
+        let json = String::from_utf8(json).unwrap();
+        assert_eq!(json, r#"{"cap":4,"elements":[0,1,2,3,4,5,6,7,8,9]}"#);
+
+        let restored: UnrolledLinkedList<i32> =
+            super::with_cap::deserialize(&mut serde_json::Deserializer::from_str(&json)).unwrap();
+        check_invariants(&restored);
+        assert_eq!(restored.cap, 4);
+        assert_eq!(node_lens(&restored), vec![4, 4, 2]);
+        assert_eq!(restored, list);
+
+        let reordered = r#"{"elements":[0,1,2,3,4,5,6,7,8,9],"cap":3}"#;
+        let restored: UnrolledLinkedList<i32> =
+            super::with_cap::deserialize(&mut serde_json::Deserializer::from_str(reordered)).unwrap();
+        check_invariants(&restored);
+        assert_eq!(restored.cap, 3);
+        assert_eq!(node_lens(&restored), vec![3, 3, 3, 1]);
+
+        let missing = r#"{"elements":[]}"#;
+        assert!(super::with_cap::deserialize::<i32, _>(&mut serde_json::Deserializer::from_str(missing)).is_err());
+    }
+
+    #[test]
+    fn hostile_cap_test() {
+        let deserialize = |json: &str| super::with_cap::deserialize::<i32, _>(&mut serde_json::Deserializer::from_str(json));
+        for cap in ["0", "65537", "1099511627776", "18446744073709551615"].iter() {
+            let err = deserialize(&format!(r#"{{"cap":{},"elements":[1]}}"#, cap)).unwrap_err();
+            assert!(err.to_string().contains("a node capacity in 1..=65536"), "{}", err);
+            assert!(deserialize(&format!(r#"{{"elements":[1],"cap":{}}}"#, cap)).is_err());
+            assert!(deserialize(&format!("[{},[1]]", cap)).is_err());
+        }
+
+        // the largest capacity is accepted, but the buffer is only as large as the elements need
+        let restored = deserialize(r#"{"cap":65536,"elements":[1,2,3]}"#).unwrap();
+        check_invariants(&restored);
+        assert_eq!(restored.cap, super::with_cap::MAX_CAP);
+        assert!(unsafe { restored.head.unwrap().as_ref().data.capacity() } < 16);
+    }
+
+    #[test]
+    fn serialize_cap_test() {
+        let serialize = |list: &UnrolledLinkedList<i32>| {
+            let mut json = vec![];
+            super::with_cap::serialize(list, &mut serde_json::Serializer::new(&mut json)).map(|_| json)
+        };
+        for cap in [0, super::with_cap::MAX_CAP + 1, 100_000].iter().copied() {
+            let list = UnrolledLinkedList::from_iter_with(cap, 1.0, 0..3);
+            let err = serialize(&list).unwrap_err();
+            assert!(err.to_string().contains("should be in 1..=65536"), "{}", err);
+        }
+
+        let list = UnrolledLinkedList::from_iter_with(super::with_cap::MAX_CAP, 1.0, 0..3);
+        let json = serialize(&list).unwrap();
+        let restored: UnrolledLinkedList<i32> =
+            super::with_cap::deserialize(&mut serde_json::Deserializer::from_slice(&json)).unwrap();
+        assert_eq!(restored.cap, super::with_cap::MAX_CAP);
+        assert_eq!(restored, list);
+    }
+}
@@ -0,0 +1,484 @@
+use core::ptr::{self, NonNull};
+use core::marker::PhantomData;
+use core::cmp::Ordering;
+use core::ops::RangeBounds;
+use core::fmt;
+use alloc::vec::Vec;
+use crate::{UnrolledLinkedList, Node};
+use crate::iters::{elem, elem_mut, Iter, IterMut};
+use allocator_api2::alloc::{Allocator, Global};
+
+impl<T, A: Allocator + Clone> UnrolledLinkedList<T, A> {
+    /// Provides a view over the given range of the list.
+    /// # Panics
+    /// Panics if the start of the range is greater than the end or if the end is greater than the len.
+    ///
+    /// This operation should compute in *O*(*n*/*cap*) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unrolled_linked_list::UnrolledLinkedList;
+    ///
+    /// let list: UnrolledLinkedList<_> = (0..10).collect();
+    /// let slice = list.range(2..6);
+    /// assert_eq!(slice.len(), 4);
+    /// assert_eq!(slice.get(1), Some(&3));
+    /// assert_eq!(slice.iter().rev().copied().collect::<Vec<_>>(), vec![5, 4, 3, 2]);
+    /// ```
+    pub fn range<R: RangeBounds<usize>>(&self, range: R) -> ListSlice<'_, T, A> {
+        ListSlice { bounds: Bounds::of_range(self, range), marker: PhantomData }
+    }
+
+    /// Provides a mutable view over the given range of the list.
+    /// # Panics
+    /// Panics if the start of the range is greater than the end or if the end is greater than the len.
+    ///
+    /// This operation should compute in *O*(*n*/*cap*) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unrolled_linked_list::UnrolledLinkedList;
+    ///
+    /// let mut list: UnrolledLinkedList<_> = vec![5, 4, 3, 2, 1, 0].into();
+    /// let mut slice = list.range_mut(1..5);
+    /// slice.sort();
+    /// assert_eq!(list.iter().copied().collect::<Vec<_>>(), vec![5, 1, 2, 3, 4, 0]);
+    /// ```
+    pub fn range_mut<R: RangeBounds<usize>>(&mut self, range: R) -> ListSliceMut<'_, T, A> {
+        ListSliceMut { bounds: Bounds::of_range(self, range), marker: PhantomData }
+    }
+}
+
+/// The positions of the first element and after the last element of a contiguous range of a list,
+/// in the same form as iterators keep them.
+pub(crate) struct Bounds<T, A: Allocator = Global> {
+    pub(crate) head: Option<NonNull<Node<T, A>>>,
+    pub(crate) index: usize,
+    pub(crate) tail: Option<NonNull<Node<T, A>>>,
+    pub(crate) back: usize,
+    pub(crate) len: usize,
+}
+
+impl<T, A: Allocator> Clone for Bounds<T, A> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T, A: Allocator> Copy for Bounds<T, A> {}
+
+impl<T, A: Allocator> Bounds<T, A> {
+    fn empty() -> Self {
+        Bounds { head: None, index: 0, tail: None, back: 0, len: 0 }
+    }
+
+    fn of_range<R: RangeBounds<usize>>(list: &UnrolledLinkedList<T, A>, range: R) -> Self where A: Clone {
+        let range = crate::to_range(range, list.len);
+        if range.is_empty() {
+            return Bounds::empty();
+        }
+        let (head, head_start) = list.find_node(range.start);
+        let (tail, tail_start) = list.find_node(range.end - 1);
+        Bounds {
+            head,
+            index: range.start - head_start,
+            tail,
+            back: range.end - tail_start,
+            len: range.len(),
+        }
+    }
+
+    /// Finds the node and the index inside it of the element with the given index in the range.
+    /// Walks the nodes from the closer end.
+    unsafe fn position(&self, idx: usize) -> (NonNull<Node<T, A>>, usize) {
+        debug_assert!(idx < self.len);
+        if idx < self.len / 2 {
+            let mut node = self.head.expect("non-empty bounds have the head");
+            let mut idx = self.index + idx;
+            while idx >= node.as_ref().data.len() {
+                idx -= node.as_ref().data.len();
+                node = node.as_ref().next.expect("the range is within the list");
+            }
+            (node, idx)
+        } else {
+            let mut node = self.tail.expect("non-empty bounds have the tail");
+            let mut rest = self.len - idx;
+            let mut back = self.back;
+            while rest > back {
+                rest -= back;
+                node = node.as_ref().prev.expect("the range is within the list");
+                back = node.as_ref().data.len();
+            }
+            (node, back - rest)
+        }
+    }
+
+    unsafe fn split_at(&self, mid: usize) -> (Self, Self) {
+        if mid > self.len {
+            panic!("mid {} should be less or equal the len {}", mid, self.len)
+        }
+        if mid == 0 {
+            return (Bounds::empty(), *self);
+        }
+        if mid == self.len {
+            return (*self, Bounds::empty());
+        }
+        let (last, last_idx) = self.position(mid - 1);
+        let (first, first_idx) = self.position(mid);
+        let front = Bounds { tail: Some(last), back: last_idx + 1, len: mid, ..*self };
+        let back = Bounds { head: Some(first), index: first_idx, len: self.len - mid, ..*self };
+        (front, back)
+    }
+
+    unsafe fn iter<'a>(&self) -> Iter<'a, T, A> {
+        Iter::from_bounds(*self)
+    }
+
+    unsafe fn iter_mut<'a>(&self) -> IterMut<'a, T, A> {
+        IterMut::from_bounds(*self)
+    }
+}
+
+/// A view over a contiguous range of a `UnrolledLinkedList`.
+///
+/// This `struct` is created by [`UnrolledLinkedList::range`]. See its documentation for more.
+pub struct ListSlice<'a, T, A: Allocator = Global> {
+    bounds: Bounds<T, A>,
+    marker: PhantomData<&'a T>,
+}
+
+unsafe impl<T: Sync, A: Allocator + Sync> Send for ListSlice<'_, T, A> {}
+
+unsafe impl<T: Sync, A: Allocator + Sync> Sync for ListSlice<'_, T, A> {}
+
+impl<T, A: Allocator> Clone for ListSlice<'_, T, A> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T, A: Allocator> Copy for ListSlice<'_, T, A> {}
+
+impl<T: fmt::Debug, A: Allocator> fmt::Debug for ListSlice<'_, T, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<'a, T, A: Allocator> ListSlice<'a, T, A> {
+    /// Returns the number of elements in the view.
+    pub fn len(&self) -> usize {
+        self.bounds.len
+    }
+
+    /// Returns `true` if the view has no elements.
+    pub fn is_empty(&self) -> bool {
+        self.bounds.len == 0
+    }
+
+    /// Returns the element with the given index in the view.
+    ///
+    /// This operation should compute in *O*(*n*/*cap*) time.
+    pub fn get(&self, index: usize) -> Option<&'a T> {
+        if index < self.bounds.len {
+            unsafe { Some(elem(self.bounds.position(index))) }
+        } else { None }
+    }
+
+    /// Provides a double-ended iterator over the view.
+    pub fn iter(&self) -> Iter<'a, T, A> {
+        unsafe { self.bounds.iter() }
+    }
+
+    /// Divides the view into two at the given index.
+    /// The first view contains the elements `[0, mid)`, the second one contains `[mid, len)`.
+    /// # Panics
+    /// Panics if `mid > len`.
+    pub fn split_at(&self, mid: usize) -> (ListSlice<'a, T, A>, ListSlice<'a, T, A>) {
+        let (front, back) = unsafe { self.bounds.split_at(mid) };
+        (ListSlice { bounds: front, marker: PhantomData }, ListSlice { bounds: back, marker: PhantomData })
+    }
+}
+
+impl<'a, T, A: Allocator + 'a> IntoIterator for ListSlice<'a, T, A> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T, A>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// A mutable view over a contiguous range of a `UnrolledLinkedList`.
+///
+/// This `struct` is created by [`UnrolledLinkedList::range_mut`]. See its documentation for more.
+pub struct ListSliceMut<'a, T, A: Allocator = Global> {
+    bounds: Bounds<T, A>,
+    marker: PhantomData<&'a mut T>,
+}
+
+unsafe impl<T: Send, A: Allocator + Send> Send for ListSliceMut<'_, T, A> {}
+
+unsafe impl<T: Sync, A: Allocator + Sync> Sync for ListSliceMut<'_, T, A> {}
+
+impl<T: fmt::Debug, A: Allocator> fmt::Debug for ListSliceMut<'_, T, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<'a, T, A: Allocator> ListSliceMut<'a, T, A> {
+    /// Returns the number of elements in the view.
+    pub fn len(&self) -> usize {
+        self.bounds.len
+    }
+
+    /// Returns `true` if the view has no elements.
+    pub fn is_empty(&self) -> bool {
+        self.bounds.len == 0
+    }
+
+    /// Provides a read-only view over the same range.
+    pub fn as_slice(&self) -> ListSlice<'_, T, A> {
+        ListSlice { bounds: self.bounds, marker: PhantomData }
+    }
+
+    /// Returns the element with the given index in the view.
+    ///
+    /// This operation should compute in *O*(*n*/*cap*) time.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.as_slice().get(index)
+    }
+
+    /// Returns the mutable reference to the element with the given index in the view.
+    ///
+    /// This operation should compute in *O*(*n*/*cap*) time.
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        if index < self.bounds.len {
+            unsafe { Some(elem_mut(self.bounds.position(index))) }
+        } else { None }
+    }
+
+    /// Provides a double-ended iterator over the view.
+    pub fn iter(&self) -> Iter<'_, T, A> {
+        unsafe { self.bounds.iter() }
+    }
+
+    /// Provides a double-ended mut iterator over the view.
+    pub fn iter_mut(&mut self) -> IterMut<'_, T, A> {
+        unsafe { self.bounds.iter_mut() }
+    }
+
+    /// Divides the view into two mutable views at the given index.
+    /// The first view contains the elements `[0, mid)`, the second one contains `[mid, len)`.
+    /// # Panics
+    /// Panics if `mid > len`.
+    pub fn split_at_mut(&mut self, mid: usize) -> (ListSliceMut<'_, T, A>, ListSliceMut<'_, T, A>) {
+        let (front, back) = unsafe { self.bounds.split_at(mid) };
+        (ListSliceMut { bounds: front, marker: PhantomData }, ListSliceMut { bounds: back, marker: PhantomData })
+    }
+
+    /// Fills the view with the clones of the given value.
+    pub fn fill(&mut self, value: T) where T: Clone {
+        for el in self.iter_mut() {
+            el.clone_from(&value);
+        }
+    }
+
+    /// Reverses the order of the elements in the view, in place.
+    pub fn reverse(&mut self) {
+        let mut iter = self.iter_mut();
+        while let (Some(front), Some(back)) = (iter.next(), iter.next_back()) {
+            core::mem::swap(front, back);
+        }
+    }
+
+    /// Sorts the view.
+    ///
+    /// The sort is stable. It sorts the positions of the elements first and then moves every element once into place,
+    /// so the elements are never copied out of the list and stay in place if the comparison panics.
+    /// It takes *O*(*n*) extra memory for the positions.
+    pub fn sort(&mut self) where T: Ord {
+        self.sort_by(T::cmp)
+    }
+
+    /// Sorts the view with a comparator function.
+    ///
+    /// The sort is stable. See [`sort`](ListSliceMut::sort) for the details.
+    pub fn sort_by<F: FnMut(&T, &T) -> Ordering>(&mut self, mut compare: F) {
+        let ptrs = self.element_ptrs();
+        let mut order: Vec<usize> = (0..ptrs.len()).collect();
+        order.sort_by(|&a, &b| unsafe { compare(&*ptrs[a], &*ptrs[b]) });
+        unsafe { permute(&ptrs, order) }
+    }
+
+    /// Sorts the view with a key extraction function.
+    ///
+    /// The sort is stable. See [`sort`](ListSliceMut::sort) for the details.
+    pub fn sort_by_key<K: Ord, F: FnMut(&T) -> K>(&mut self, mut f: F) {
+        self.sort_by(|a, b| f(a).cmp(&f(b)))
+    }
+
+    /// Collects the pointers to the elements of the view.
+    pub(crate) fn element_ptrs(&mut self) -> Vec<*mut T> {
+        self.iter_mut().map(|el| el as *mut T).collect()
+    }
+}
+
+impl<'a, T, A: Allocator + 'a> IntoIterator for ListSliceMut<'a, T, A> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T, A>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        unsafe { self.bounds.iter_mut() }
+    }
+}
+
+/// Moves the elements so that the element at `ptrs[order[i]]` ends up at `ptrs[i]`.
+/// Every cycle of the permutation is rotated by swaps, so nothing is left duplicated if a swap is interrupted.
+pub(crate) unsafe fn permute<T>(ptrs: &[*mut T], mut order: Vec<usize>) {
+    for start in 0..order.len() {
+        let mut cur = start;
+        loop {
+            let next = core::mem::replace(&mut order[cur], cur);
+            if next == start || next == cur {
+                break;
+            }
+            ptr::swap(ptrs[cur], ptrs[next]);
+            cur = next;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::format;
+    use std::string::ToString;
+    use std::vec::Vec;
+    use crate::UnrolledLinkedList;
+    use crate::tests::check_invariants;
+
+    fn to_vec(list: &UnrolledLinkedList<i32>) -> Vec<i32> {
+        check_invariants(list);
+        list.iter().cloned().collect()
+    }
+
+    #[test]
+    fn range_test() {
+        let list = UnrolledLinkedList::from_iter_with(4, 0.5, 0..20);
+        for start in 0..=20 {
+            for end in start..=20 {
+                let slice = list.range(start..end);
+                assert_eq!(slice.len(), end - start);
+                assert_eq!(slice.iter().copied().collect::<Vec<_>>(), (start..end).collect::<Vec<_>>());
+                assert_eq!(slice.iter().rev().copied().collect::<Vec<_>>(), (start..end).rev().collect::<Vec<_>>());
+                for i in 0..slice.len() {
+                    assert_eq!(slice.get(i), Some(&(start + i)));
+                }
+                assert_eq!(slice.get(slice.len()), None);
+                for mid in 0..=slice.len() {
+                    let (front, back) = slice.split_at(mid);
+                    assert_eq!(front.into_iter().chain(back).copied().collect::<Vec<_>>(), (start..end).collect::<Vec<_>>());
+                    assert_eq!(front.iter().next_back(), mid.checked_sub(1).map(|i| &list[start + i]));
+                    assert_eq!(back.len(), end - start - mid);
+                }
+            }
+        }
+        assert_eq!(format!("{:?}", list.range(..3)), "[0, 1, 2]");
+    }
+
+    #[test]
+    #[should_panic]
+    fn range_out_of_bounds_test() {
+        let list: UnrolledLinkedList<_> = (0..10).collect();
+        list.range(3..11);
+    }
+
+    #[test]
+    fn index_test() {
+        let mut list = UnrolledLinkedList::from_iter_with(4, 0.5, 0..20);
+        for i in 0..20 {
+            assert_eq!(list[i], i as i32);
+            list[i] *= 2;
+        }
+        assert_eq!(to_vec(&list), (0..20).map(|i| i * 2).collect::<Vec<_>>());
+    }
+
+    #[test]
+    #[should_panic]
+    fn index_out_of_bounds_test() {
+        let list: UnrolledLinkedList<_> = (0..10).collect();
+        let _ = list[10];
+    }
+
+    #[test]
+    fn range_mut_test() {
+        let mut list = UnrolledLinkedList::from_iter_with(4, 0.5, 0..20);
+        let mut slice = list.range_mut(3..17);
+        slice.reverse();
+        *slice.get_mut(0).unwrap() = 100;
+        {
+            let (mut front, mut back) = slice.split_at_mut(5);
+            front.fill(-1);
+            back.reverse();
+            *back.get_mut(8).unwrap() += 1;
+        }
+        let mut expected: Vec<_> = (0..20).collect();
+        expected[3..17].reverse();
+        expected[3] = 100;
+        expected[3..8].iter_mut().for_each(|el| *el = -1);
+        expected[8..17].reverse();
+        expected[16] += 1;
+        assert_eq!(slice.get(5), Some(&expected[8]));
+        assert_eq!(slice.iter().copied().collect::<Vec<_>>(), expected[3..17]);
+        assert_eq!(to_vec(&list), expected);
+
+        for len in 0..12 {
+            let mut list = UnrolledLinkedList::from_iter_with(3, 0.7, 0..20);
+            list.range_mut(5..5 + len).reverse();
+            let mut expected: Vec<_> = (0..20).collect();
+            expected[5..5 + len].reverse();
+            assert_eq!(to_vec(&list), expected);
+        }
+    }
+
+    #[test]
+    fn sort_test() {
+        let mut seed = 17u32;
+        let values: Vec<i32> = (0..60).map(|_| {
+            seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+            (seed >> 16) as i32 % 10
+        }).collect();
+        let mut list = UnrolledLinkedList::with_capacity(4);
+        list.extend(values.iter().enumerate().map(|(i, v)| (*v, i)));
+
+        list.range_mut(10..50).sort_by_key(|el| el.0);
+        let mut expected: Vec<_> = values.iter().copied().zip(0..).collect();
+        expected[10..50].sort_by_key(|el| el.0);
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), expected);
+
+        list.range_mut(..).sort();
+        expected.sort();
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), expected);
+    }
+
+    #[test]
+    fn sort_panic_test() {
+        let mut list: UnrolledLinkedList<_> = (0..30).rev().map(|i| i.to_string()).collect();
+        let mut calls = 0;
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            list.range_mut(..).sort_by(|a, b| {
+                calls += 1;
+                if calls == 20 {
+                    panic!("compare")
+                }
+                a.cmp(b)
+            })
+        }));
+        assert!(result.is_err());
+        check_invariants(&list);
+        assert_eq!(list.iter().cloned().collect::<Vec<_>>(), (0..30).rev().map(|i| i.to_string()).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,501 @@
+use core::cmp::Ordering;
+use core::ptr::NonNull;
+use alloc::vec::Vec;
+use crate::{UnrolledLinkedList, Node};
+use crate::slice::permute;
+use allocator_api2::alloc::Allocator;
+
+impl<T, A: Allocator + Clone> UnrolledLinkedList<T, A> {
+    /// Sorts the list.
+    ///
+    /// The sort is stable. The buffer of every node is sorted in place first, then the positions of the elements
+    /// in the sorted nodes are merged by halves, so *m* nodes take log(*m*) levels of merging, and every element
+    /// is moved once into place. The nodes keep their allocations and lengths.
+    /// If the comparison panics, every element stays in the list exactly once, in an unspecified order.
+    ///
+    /// This operation should compute in *O*(*n* \* log(*n*)) time and takes *O*(*n*) extra memory:
+    /// a pointer and a position for every element and a copy of the positions in the left half of a merge.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unrolled_linked_list::UnrolledLinkedList;
+    ///
+    /// let mut list: UnrolledLinkedList<_> = vec![5, 4, 1, 3, 2].into();
+    /// list.sort();
+    /// assert_eq!(list.iter().copied().collect::<Vec<_>>(), vec![1, 2, 3, 4, 5]);
+    /// ```
+    pub fn sort(&mut self) where T: Ord {
+        self.sort_by(T::cmp)
+    }
+
+    /// Sorts the list with a comparator function.
+    ///
+    /// The sort is stable. See [`sort`](UnrolledLinkedList::sort) for the details.
+    pub fn sort_by<F: FnMut(&T, &T) -> Ordering>(&mut self, mut compare: F) {
+        for chunk in self.chunks_mut() {
+            chunk.sort_by(&mut compare);
+        }
+        self.merge_nodes(compare);
+    }
+
+    /// Sorts the list with a key extraction function.
+    ///
+    /// The sort is stable. See [`sort`](UnrolledLinkedList::sort) for the details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unrolled_linked_list::UnrolledLinkedList;
+    ///
+    /// let mut list: UnrolledLinkedList<_> = vec![-5i32, 4, 1, -3, 2].into();
+    /// list.sort_by_key(|el| el.abs());
+    /// assert_eq!(list.iter().copied().collect::<Vec<_>>(), vec![1, 2, -3, 4, -5]);
+    /// ```
+    pub fn sort_by_key<K: Ord, F: FnMut(&T) -> K>(&mut self, mut f: F) {
+        self.sort_by(|a, b| f(a).cmp(&f(b)))
+    }
+
+    /// Sorts the list, but might not preserve the order of equal elements.
+    ///
+    /// The buffers of the nodes are sorted with the unstable sort, the rest is the same as in
+    /// [`sort`](UnrolledLinkedList::sort), including the behaviour on a panic.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unrolled_linked_list::UnrolledLinkedList;
+    ///
+    /// let mut list: UnrolledLinkedList<_> = vec![5, 4, 1, 3, 2].into();
+    /// list.sort_unstable();
+    /// assert_eq!(list.iter().copied().collect::<Vec<_>>(), vec![1, 2, 3, 4, 5]);
+    /// ```
+    pub fn sort_unstable(&mut self) where T: Ord {
+        self.sort_unstable_by(T::cmp)
+    }
+
+    /// Sorts the list with a comparator function, but might not preserve the order of equal elements.
+    ///
+    /// See [`sort_unstable`](UnrolledLinkedList::sort_unstable) for the details.
+    pub fn sort_unstable_by<F: FnMut(&T, &T) -> Ordering>(&mut self, mut compare: F) {
+        for chunk in self.chunks_mut() {
+            chunk.sort_unstable_by(&mut compare);
+        }
+        self.merge_nodes(compare);
+    }
+
+    /// Sorts the list with a key extraction function, but might not preserve the order of equal elements.
+    ///
+    /// See [`sort_unstable`](UnrolledLinkedList::sort_unstable) for the details.
+    pub fn sort_unstable_by_key<K: Ord, F: FnMut(&T) -> K>(&mut self, mut f: F) {
+        self.sort_unstable_by(|a, b| f(a).cmp(&f(b)))
+    }
+
+    /// Reorders the list such that the element at `index` is at its final sorted position,
+    /// all elements before it are less or equal to it and all elements after it are greater or equal to it.
+    /// Returns the element at `index`.
+    ///
+    /// The positions of the elements are reordered first and then every element is moved once into place,
+    /// so if the comparison panics, the list is left untouched.
+    ///
+    /// This operation should compute in *O*(*n*) time on average.
+    ///
+    /// # Panics
+    /// Panics if `index` is greater or equal the len.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unrolled_linked_list::UnrolledLinkedList;
+    ///
+    /// let mut list: UnrolledLinkedList<_> = vec![-5, 4, 2, -3, 1].into();
+    /// assert_eq!(*list.select_nth_unstable(1), -3);
+    /// assert!(list.iter().take(1).all(|el| *el <= -3));
+    /// assert!(list.iter().skip(2).all(|el| *el >= -3));
+    /// ```
+    pub fn select_nth_unstable(&mut self, index: usize) -> &mut T where T: Ord {
+        self.select_nth_unstable_by(index, T::cmp)
+    }
+
+    /// Reorders the list with a comparator function such that the element at `index` is at its final sorted position.
+    /// Returns the element at `index`.
+    ///
+    /// See [`select_nth_unstable`](UnrolledLinkedList::select_nth_unstable) for the details.
+    ///
+    /// # Panics
+    /// Panics if `index` is greater or equal the len.
+    pub fn select_nth_unstable_by<F: FnMut(&T, &T) -> Ordering>(&mut self, index: usize, mut compare: F) -> &mut T {
+        if index >= self.len {
+            panic!("index {} should be less than the len {}", index, self.len)
+        }
+        let ptrs = self.range_mut(..).element_ptrs();
+        let mut order: Vec<usize> = (0..ptrs.len()).collect();
+        order.select_nth_unstable_by(index, |&a, &b| unsafe { compare(&*ptrs[a], &*ptrs[b]) });
+        let nth = ptrs[index];
+        unsafe {
+            permute(&ptrs, order);
+            &mut *nth
+        }
+    }
+
+    /// Reorders the list with a key extraction function such that the element at `index` is at its final sorted position.
+    /// Returns the element at `index`.
+    ///
+    /// See [`select_nth_unstable`](UnrolledLinkedList::select_nth_unstable) for the details.
+    ///
+    /// # Panics
+    /// Panics if `index` is greater or equal the len.
+    pub fn select_nth_unstable_by_key<K: Ord, F: FnMut(&T) -> K>(&mut self, index: usize, mut f: F) -> &mut T {
+        self.select_nth_unstable_by(index, |a, b| f(a).cmp(&f(b)))
+    }
+
+    /// Binary searches the sorted list for the given element.
+    ///
+    /// If the value is found, returns `Ok` with the index of the matching element.
+    /// If there are multiple matches, any one of them can be returned.
+    /// If the value is not found, returns `Err` with the index where the element could be inserted keeping the order.
+    ///
+    /// The node holding the element is found by comparing with the last elements of the nodes,
+    /// then the buffer of that node is binary searched.
+    ///
+    /// This operation should compute in *O*(log(*n*)) time if the list is indexed,
+    /// see [`set_indexed`](UnrolledLinkedList::set_indexed), and in *O*(*n*/*cap* + log(*cap*)) time otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unrolled_linked_list::UnrolledLinkedList;
+    ///
+    /// let list = UnrolledLinkedList::from_iter_with(4, 1.0, vec![0, 1, 1, 1, 1, 2, 3, 5, 8, 13, 21, 34, 55]);
+    /// assert_eq!(list.binary_search(&13), Ok(9));
+    /// assert_eq!(list.binary_search(&4), Err(7));
+    /// assert_eq!(list.binary_search(&100), Err(13));
+    /// let found = list.binary_search(&1);
+    /// assert!(matches!(found, Ok(1..=4)));
+    /// ```
+    pub fn binary_search(&self, x: &T) -> Result<usize, usize> where T: Ord {
+        self.binary_search_by(|el| el.cmp(x))
+    }
+
+    /// Binary searches the sorted list with a comparator function.
+    ///
+    /// The comparator returns the order of the element relative to the target.
+    /// See [`binary_search`](UnrolledLinkedList::binary_search) for the details.
+    pub fn binary_search_by<F: FnMut(&T) -> Ordering>(&self, mut f: F) -> Result<usize, usize> {
+        match self.partition_node(|el| f(el) == Ordering::Less) {
+            (Some(node), start) => unsafe {
+                node.as_ref().data.binary_search_by(f).map(|i| start + i).map_err(|i| start + i)
+            },
+            (None, _) => Err(self.len),
+        }
+    }
+
+    /// Binary searches the sorted list with a key extraction function.
+    ///
+    /// See [`binary_search`](UnrolledLinkedList::binary_search) for the details.
+    pub fn binary_search_by_key<B: Ord, F: FnMut(&T) -> B>(&self, b: &B, mut f: F) -> Result<usize, usize> {
+        self.binary_search_by(|el| f(el).cmp(b))
+    }
+
+    /// Returns the index of the partition point of the list according to the given predicate,
+    /// that is the index of the first element for which the predicate returns `false`.
+    ///
+    /// The list is expected to be partitioned: all elements satisfying the predicate go before the rest.
+    /// See [`binary_search`](UnrolledLinkedList::binary_search) for the complexity.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unrolled_linked_list::UnrolledLinkedList;
+    ///
+    /// let list: UnrolledLinkedList<_> = (0..100).collect();
+    /// assert_eq!(list.partition_point(|el| *el < 42), 42);
+    /// ```
+    pub fn partition_point<P: FnMut(&T) -> bool>(&self, mut pred: P) -> usize {
+        match self.partition_node(&mut pred) {
+            (Some(node), start) => unsafe { start + node.as_ref().data.partition_point(pred) },
+            (None, _) => self.len,
+        }
+    }
+
+    /// Finds the first node with the last element not satisfying the predicate,
+    /// returns the node and the index of its first element.
+    fn partition_node<P: FnMut(&T) -> bool>(&self, mut pred: P) -> (Option<NonNull<Node<T, A>>>, usize) {
+        let mut last_matches = |node: &NonNull<Node<T, A>>| unsafe {
+            matches!(node.as_ref().data.last(), Some(el) if pred(el))
+        };
+        if let Some(index) = self.index.as_ref().filter(|index| !index.is_dirty()) {
+            return index.partition_point(&mut last_matches);
+        }
+        let mut start = 0;
+        let mut next_node = self.head;
+        while let Some(node) = next_node {
+            if !last_matches(&node) {
+                return (Some(node), start);
+            }
+            unsafe {
+                start += node.as_ref().data.len();
+                next_node = node.as_ref().next;
+            }
+        }
+        (None, self.len)
+    }
+
+    /// Merges the sorted nodes keeping the order of equal elements.
+    ///
+    /// Every node is a run of positions, the halves of the runs are merged recursively,
+    /// so the small merges are done while their elements are still in the cache.
+    /// Nothing is moved before the last comparison, then the elements are permuted into place.
+    fn merge_nodes<F: FnMut(&T, &T) -> Ordering>(&mut self, mut compare: F) {
+        if self.head == self.tail {
+            return;
+        }
+        let mut ptrs = Vec::with_capacity(self.len);
+        // the end of every run
+        let mut runs = Vec::new();
+        for chunk in self.chunks_mut() {
+            ptrs.extend(chunk.iter_mut().map(|el| el as *mut T));
+            runs.push(ptrs.len());
+        }
+        let mut order: Vec<usize> = (0..ptrs.len()).collect();
+        let mut left = Vec::with_capacity(ptrs.len() / 2);
+        let mut is_less = |a: usize, b: usize| unsafe { compare(&*ptrs[a], &*ptrs[b]) == Ordering::Less };
+        merge_runs(&mut order, &mut left, 0, &runs, &mut is_less);
+        unsafe { permute(&ptrs, order) }
+    }
+}
+
+/// Merges the sorted runs of positions from `start` up to the `ends` of the runs.
+/// The `left` buffer keeps the copy of the left half while merging.
+fn merge_runs<F>(order: &mut [usize], left: &mut Vec<usize>, start: usize, ends: &[usize], is_less: &mut F)
+    where F: FnMut(usize, usize) -> bool {
+    if ends.len() < 2 {
+        return;
+    }
+    let half = ends.len() / 2;
+    let mid = ends[half - 1];
+    let end = ends[ends.len() - 1];
+    merge_runs(order, left, start, &ends[..half], is_less);
+    merge_runs(order, left, mid, &ends[half..], is_less);
+    // the runs that are already in order are left as they are after a single comparison
+    if !is_less(order[mid], order[mid - 1]) {
+        return;
+    }
+    left.clear();
+    left.extend_from_slice(&order[start..mid]);
+    let (mut i, mut j, mut k) = (0, mid, start);
+    while i < left.len() && j < end {
+        // ties are taken from the left run
+        if is_less(order[j], left[i]) {
+            order[k] = order[j];
+            j += 1;
+        } else {
+            order[k] = left[i];
+            i += 1;
+        }
+        k += 1;
+    }
+    // the rest of the right run is in place already
+    order[k..k + left.len() - i].copy_from_slice(&left[i..]);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+    use std::string::{String, ToString};
+    use std::vec::Vec;
+    use crate::UnrolledLinkedList;
+    use crate::tests::{check_invariants, node_lens};
+
+    fn values(len: usize, modulo: u32) -> Vec<(u32, usize)> {
+        let mut seed = 31u32;
+        (0..len).map(|i| {
+            seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+            ((seed >> 16) % modulo, i)
+        }).collect()
+    }
+
+    #[test]
+    fn sort_test() {
+        for cap in 1..9 {
+            let values = values(100, 10);
+            let mut list = UnrolledLinkedList::with_capacity(cap);
+            for (i, v) in values.iter().enumerate() {
+                list.insert(i / 2, *v);
+            }
+            let mut expected: Vec<_> = list.iter().copied().collect();
+            let lens = node_lens(&list);
+
+            list.sort_by_key(|el| el.0);
+            expected.sort_by_key(|el| el.0);
+            check_invariants(&list);
+            assert_eq!(node_lens(&list), lens);
+            assert_eq!(list.iter().copied().collect::<Vec<_>>(), expected);
+
+            list.sort_by(|a, b| b.1.cmp(&a.1));
+            expected.sort_by_key(|el| core::cmp::Reverse(el.1));
+            assert_eq!(list.iter().copied().collect::<Vec<_>>(), expected);
+
+            list.sort();
+            expected.sort();
+            assert_eq!(list.iter().copied().collect::<Vec<_>>(), expected);
+        }
+        let mut empty = UnrolledLinkedList::<i32>::new();
+        empty.sort();
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn merge_test() {
+        // Miri runs shorter lists
+        let cases: &[(usize, usize)] = if cfg!(miri) {
+            &[(200, 8), (10, 3)]
+        } else {
+            &[(1024, 8), (1000, 7), (4096, 64), (10, 3)]
+        };
+        for (len, cap) in cases.iter().copied() {
+            // every node is sorted already, so the comparisons beyond checking the nodes come from the merge
+            let mut values = values(len, 100);
+            for chunk in values.chunks_mut(cap) {
+                chunk.sort_by_key(|el| el.0);
+            }
+            let mut list = UnrolledLinkedList::from_iter_with(cap, 1.0, values.iter().copied());
+            let nodes = node_lens(&list).len();
+            let mut calls = 0;
+            list.sort_by(|a, b| {
+                calls += 1;
+                a.0.cmp(&b.0)
+            });
+            values.sort_by_key(|el| el.0);
+            check_invariants(&list);
+            assert_eq!(list.iter().copied().collect::<Vec<_>>(), values);
+            let levels = nodes.next_power_of_two().trailing_zeros() as usize;
+            assert!(calls <= nodes * (cap - 1) + len * levels, "{} comparisons for {} nodes", calls, nodes);
+
+            // the nodes in order are merged with one comparison per pair
+            let mut calls = 0;
+            list.sort_by(|a, b| {
+                calls += 1;
+                a.0.cmp(&b.0)
+            });
+            assert!(calls <= nodes * cap, "{} comparisons for {} nodes", calls, nodes);
+        }
+    }
+
+    #[test]
+    fn sort_unstable_test() {
+        let values = values(200, 1000);
+        let mut list = UnrolledLinkedList::with_capacity(7);
+        list.extend(values.iter().map(|v| v.0));
+        let mut expected: Vec<_> = values.iter().map(|v| v.0).collect();
+
+        list.sort_unstable();
+        expected.sort_unstable();
+        check_invariants(&list);
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), expected);
+
+        list.sort_unstable_by_key(|el| u32::MAX - el);
+        expected.reverse();
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), expected);
+    }
+
+    #[test]
+    fn select_nth_unstable_test() {
+        let values: Vec<_> = values(150, 40).into_iter().map(|v| v.0).collect();
+        let mut sorted = values.clone();
+        sorted.sort_unstable();
+        for index in [0, 1, 37, 75, 148, 149].iter().copied() {
+            let mut list = UnrolledLinkedList::with_capacity(6);
+            list.extend(values.iter().copied());
+            let nth = *list.select_nth_unstable(index);
+            check_invariants(&list);
+            assert_eq!(nth, sorted[index]);
+            assert_eq!(list[index], nth);
+            assert!(list.iter().take(index).all(|el| *el <= nth));
+            assert!(list.iter().skip(index + 1).all(|el| *el >= nth));
+            let mut all: Vec<_> = list.iter().copied().collect();
+            all.sort_unstable();
+            assert_eq!(all, sorted);
+        }
+
+        let mut list: UnrolledLinkedList<_> = (0..20).collect();
+        *list.select_nth_unstable_by_key(0, |el| 19 - el) += 100;
+        assert_eq!(list[0], 119);
+    }
+
+    #[test]
+    fn binary_search_test() {
+        let mut sorted: Vec<u32> = values(300, 100).into_iter().map(|v| v.0 * 2).collect();
+        sorted.sort_unstable();
+        for indexed in [false, true].iter().copied() {
+            for cap in [1, 2, 5, 16].iter().copied() {
+                let mut list = UnrolledLinkedList::with_capacity(cap);
+                list.set_indexed(indexed);
+                for (i, el) in sorted.iter().enumerate() {
+                    list.insert(i / 2, *el);
+                }
+                list.sort();
+                check_invariants(&list);
+                // Miri looks up a sample of the values
+                for x in (0..202u32).step_by(if cfg!(miri) { 9 } else { 1 }) {
+                    match list.binary_search(&x) {
+                        Ok(i) => assert_eq!(list[i], x),
+                        Err(i) => assert_eq!(Err(i), sorted.binary_search(&x)),
+                    }
+                    assert_eq!(list.partition_point(|el| *el < x), sorted.partition_point(|el| *el < x));
+                    assert_eq!(list.binary_search_by_key(&(x + 1), |el| el + 1).is_ok(), sorted.contains(&x));
+                }
+            }
+        }
+
+        let mut list = UnrolledLinkedList::with_capacity(8);
+        list.extend(0..4096);
+        list.set_indexed(true);
+        let mut calls = 0;
+        assert_eq!(list.binary_search_by(|el| {
+            calls += 1;
+            el.cmp(&1234)
+        }), Ok(1234));
+        // about 2 ln(512) comparisons along a path of the index over the nodes and log(8) inside the node
+        assert!(calls <= 20 + 4, "{} comparisons", calls);
+
+        let empty = UnrolledLinkedList::<u32>::new();
+        assert_eq!(empty.binary_search(&1), Err(0));
+        assert_eq!(empty.partition_point(|_| true), 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn select_nth_out_of_bounds_test() {
+        let mut list: UnrolledLinkedList<_> = (0..5).collect();
+        list.select_nth_unstable(5);
+    }
+
+    #[test]
+    fn sort_panic_test() {
+        let items: Vec<Rc<String>> = (0..40).rev().map(|i| Rc::new(i.to_string())).collect();
+        for limit in [3, 20, 45, 70].iter().copied() {
+            let mut list = UnrolledLinkedList::with_capacity(4);
+            list.extend(items.iter().cloned());
+            let mut calls = 0;
+            let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                list.sort_by(|a, b| {
+                    calls += 1;
+                    if calls == limit {
+                        panic!("compare")
+                    }
+                    a.cmp(b)
+                })
+            }));
+            assert!(result.is_err());
+            check_invariants(&list);
+            assert!(items.iter().all(|item| Rc::strong_count(item) == 2));
+            let mut all: Vec<_> = list.iter().cloned().collect();
+            all.sort();
+            let mut expected = items.clone();
+            expected.sort();
+            assert_eq!(all, expected);
+        }
+        assert!(items.iter().all(|item| Rc::strong_count(item) == 1));
+    }
+}
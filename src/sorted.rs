@@ -0,0 +1,364 @@
+//! The list keeping its elements sorted.
+//!
+//! [`SortedUnrolledList`] is an ordered bag over [`UnrolledLinkedList`]: the position of every element is found
+//! by the binary search over the node bounds (see [`UnrolledLinkedList::binary_search`]), and the list is indexed
+//! (see [`UnrolledLinkedList::set_indexed`]), so the positional access and the search take *O*(log(*n*)) expected time
+//! and the insertion and the removal take *O*(log(*n*) + *cap*) expected time.
+//! In the dedup mode it keeps at most one element of every value and behaves like a set.
+//!
+//! # Example
+//! ```rust
+//! use unrolled_linked_list::sorted::SortedUnrolledList;
+//!
+//! let mut scores = SortedUnrolledList::new();
+//! for score in [30, 10, 20, 10, 50].iter().copied() {
+//!     scores.insert(score);
+//! }
+//! assert_eq!(scores.iter().copied().collect::<Vec<_>>(), vec![10, 10, 20, 30, 50]);
+//! assert_eq!(scores.rank(&30), 3);
+//! assert_eq!(scores.nth(2), Some(&20));
+//! assert_eq!(scores.range(15..=30).iter().count(), 2);
+//! ```
+
+use core::fmt;
+use core::iter::{self, FromIterator};
+use core::ops::{Bound, RangeBounds};
+use allocator_api2::alloc::{Allocator, Global};
+use crate::UnrolledLinkedList;
+use crate::iters::{IntoIter, Iter};
+use crate::slice::ListSlice;
+
+/// The list keeping its elements sorted, duplicates are allowed unless the dedup mode is on.
+pub struct SortedUnrolledList<T, A: Allocator + Clone = Global> {
+    list: UnrolledLinkedList<T, A>,
+    dedup: bool,
+}
+
+impl<T: Ord> SortedUnrolledList<T> {
+    /// Creates an empty sorted list setting the size of node to 8.
+    pub fn new() -> Self {
+        SortedUnrolledList::with_capacity(8)
+    }
+
+    /// Creates an empty sorted list. Capacity defines the size of the node.
+    pub fn with_capacity(cap: usize) -> Self {
+        SortedUnrolledList::with_capacity_in(cap, Global)
+    }
+}
+
+impl<T: Ord, A: Allocator + Clone> SortedUnrolledList<T, A> {
+    /// Creates an empty sorted list allocating the nodes and their buffers with the given allocator.
+    /// Capacity defines the size of the node. The underlying list is indexed.
+    pub fn with_capacity_in(cap: usize, alloc: A) -> Self {
+        let mut list = UnrolledLinkedList::with_capacity_in(cap, alloc);
+        list.set_indexed(true);
+        SortedUnrolledList { list, dedup: false }
+    }
+
+    /// Turns on or off the dedup mode. Turning it on removes the duplicates keeping the first of the equal elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use unrolled_linked_list::sorted::SortedUnrolledList;
+    ///
+    /// let mut set: SortedUnrolledList<_> = vec![3, 1, 3, 2, 1].into_iter().collect();
+    /// set.set_dedup(true);
+    /// assert_eq!(set.iter().copied().collect::<Vec<_>>(), vec![1, 2, 3]);
+    /// assert!(!set.insert(2));
+    /// assert_eq!(set.len(), 3);
+    /// ```
+    pub fn set_dedup(&mut self, dedup: bool) {
+        if dedup && !self.dedup {
+            let empty = self.list.empty_like();
+            let mut iter = core::mem::replace(&mut self.list, empty).into_iter().peekable();
+            self.list.extend(iter::from_fn(|| {
+                let el = iter.next()?;
+                while iter.next_if(|next| *next == el).is_some() {}
+                Some(el)
+            }));
+        }
+        self.dedup = dedup;
+    }
+
+    /// Returns `true` if the list keeps at most one element of every value.
+    pub fn is_dedup(&self) -> bool {
+        self.dedup
+    }
+
+    /// Inserts the element after the equal ones keeping the list sorted.
+    /// In the dedup mode the element is not inserted if there is an equal one.
+    /// Returns `true` if the element has been inserted.
+    ///
+    /// This operation should compute in *O*(log(*n*) + *cap*) expected time.
+    pub fn insert(&mut self, value: T) -> bool {
+        let pos = if self.dedup {
+            match self.list.binary_search(&value) {
+                Ok(_) => return false,
+                Err(pos) => pos,
+            }
+        } else {
+            self.list.partition_point(|el| *el <= value)
+        };
+        self.list.insert(pos, value);
+        true
+    }
+
+    /// Removes one element equal to the value. Returns the removed element if there was one.
+    ///
+    /// This operation should compute in *O*(log(*n*) + *cap*) expected time.
+    pub fn remove(&mut self, value: &T) -> Option<T> {
+        self.list.binary_search(value).ok().map(|pos| self.list.remove(pos))
+    }
+
+    /// Returns `true` if the list contains an element equal to the value.
+    ///
+    /// This operation should compute in *O*(log(*n*)) expected time.
+    pub fn contains(&self, value: &T) -> bool {
+        self.list.binary_search(value).is_ok()
+    }
+
+    /// Returns the number of elements less than the value, that is the index of the first element
+    /// not less than the value.
+    ///
+    /// This operation should compute in *O*(log(*n*)) expected time.
+    pub fn rank(&self, value: &T) -> usize {
+        self.list.partition_point(|el| el < value)
+    }
+
+    /// Returns the element with the given index in the sorted order.
+    ///
+    /// This operation should compute in *O*(log(*n*)) expected time.
+    pub fn nth(&self, index: usize) -> Option<&T> {
+        self.list.get(index)
+    }
+
+    /// Provides a view over the elements within the given range of values.
+    ///
+    /// The view is empty if the range is empty.
+    ///
+    /// This operation should compute in *O*(log(*n*)) expected time.
+    pub fn range<R: RangeBounds<T>>(&self, range: R) -> ListSlice<'_, T, A> {
+        let start = match range.start_bound() {
+            Bound::Included(start) => self.list.partition_point(|el| el < start),
+            Bound::Excluded(start) => self.list.partition_point(|el| el <= start),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(end) => self.list.partition_point(|el| el <= end),
+            Bound::Excluded(end) => self.list.partition_point(|el| el < end),
+            Bound::Unbounded => self.list.len(),
+        };
+        self.list.range(start..end.max(start))
+    }
+}
+
+impl<T, A: Allocator + Clone> SortedUnrolledList<T, A> {
+    /// Returns the number of elements.
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    /// Returns `true` if the list contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    /// Returns the least element.
+    pub fn first(&self) -> Option<&T> {
+        self.list.front()
+    }
+
+    /// Returns the greatest element.
+    pub fn last(&self) -> Option<&T> {
+        self.list.back()
+    }
+
+    /// Provides a double-ended iterator in the sorted order.
+    pub fn iter(&self) -> Iter<'_, T, A> {
+        self.list.iter()
+    }
+
+    /// Removes all elements.
+    pub fn clear(&mut self) {
+        self.list.clear()
+    }
+
+    /// Returns the underlying list.
+    pub fn as_list(&self) -> &UnrolledLinkedList<T, A> {
+        &self.list
+    }
+
+    /// Turns into the underlying list.
+    pub fn into_list(self) -> UnrolledLinkedList<T, A> {
+        self.list
+    }
+}
+
+impl<T: Ord> Default for SortedUnrolledList<T> {
+    fn default() -> Self {
+        SortedUnrolledList::new()
+    }
+}
+
+impl<T: Clone, A: Allocator + Clone> Clone for SortedUnrolledList<T, A> {
+    fn clone(&self) -> Self {
+        SortedUnrolledList { list: self.list.clone(), dedup: self.dedup }
+    }
+}
+
+impl<T: fmt::Debug, A: Allocator + Clone> fmt::Debug for SortedUnrolledList<T, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<T: PartialEq, A: Allocator + Clone> PartialEq for SortedUnrolledList<T, A> {
+    fn eq(&self, other: &Self) -> bool {
+        self.list == other.list
+    }
+}
+
+impl<T: Eq, A: Allocator + Clone> Eq for SortedUnrolledList<T, A> {}
+
+impl<T: Ord, A: Allocator + Clone> Extend<T> for SortedUnrolledList<T, A> {
+    fn extend<I: IntoIterator<Item=T>>(&mut self, iter: I) {
+        for el in iter {
+            self.insert(el);
+        }
+    }
+}
+
+impl<T: Ord> FromIterator<T> for SortedUnrolledList<T> {
+    /// Collects the elements into a list and sorts it in place.
+    fn from_iter<I: IntoIterator<Item=T>>(iter: I) -> Self {
+        let mut sorted = SortedUnrolledList::new();
+        sorted.list.extend(iter);
+        sorted.list.sort();
+        sorted
+    }
+}
+
+impl<T, A: Allocator + Clone> IntoIterator for SortedUnrolledList<T, A> {
+    type Item = T;
+    type IntoIter = IntoIter<T, A>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.list.into_iter()
+    }
+}
+
+impl<'a, T, A: Allocator + Clone> IntoIterator for &'a SortedUnrolledList<T, A> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T, A>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+    use std::ops::Bound;
+    use std::vec;
+    use std::vec::Vec;
+    use crate::sorted::SortedUnrolledList;
+    use crate::tests::check_invariants;
+
+    #[test]
+    fn bag_test() {
+        let mut sorted = SortedUnrolledList::with_capacity(4);
+        let mut model: Vec<(u32, u32)> = vec![];
+        let mut seed = 5u32;
+        // Miri runs fewer operations
+        for i in 0..if cfg!(miri) { 150 } else { 600 } {
+            seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+            let value = (seed >> 16) % 50;
+            if (seed >> 8) % 3 == 1 && !model.is_empty() {
+                let removed = sorted.remove(&(value, 0)).is_some();
+                let pos = model.iter().position(|el| *el == (value, 0));
+                assert_eq!(removed, pos.is_some());
+                if let Some(pos) = pos {
+                    model.remove(pos);
+                }
+            } else {
+                // the second field keeps the insertion order of equal values observable
+                let el = (value, i % 2);
+                assert!(sorted.insert(el));
+                let pos = model.partition_point(|m| *m <= el);
+                model.insert(pos, el);
+            }
+            check_invariants(sorted.as_list());
+        }
+        assert_eq!(sorted.iter().copied().collect::<Vec<_>>(), model);
+        for value in 0..51 {
+            let el = (value, 0);
+            assert_eq!(sorted.contains(&el), model.contains(&el));
+            assert_eq!(sorted.rank(&el), model.partition_point(|m| *m < el));
+        }
+        for (i, el) in model.iter().enumerate() {
+            assert_eq!(sorted.nth(i), Some(el));
+        }
+        assert_eq!(sorted.nth(model.len()), None);
+        assert_eq!(sorted.first(), model.first());
+        assert_eq!(sorted.last(), model.last());
+    }
+
+    #[test]
+    fn indexed_test() {
+        let mut sorted = SortedUnrolledList::with_capacity(8);
+        for i in 0..if cfg!(miri) { 500 } else { 5_000u32 } {
+            sorted.insert(i.wrapping_mul(2_654_435_761) % 1_000);
+            if i % 3 == 0 {
+                sorted.remove(&(i % 1_000));
+            }
+        }
+        check_invariants(sorted.as_list());
+        // every split and merge has been applied to the index in place
+        assert_eq!(sorted.list.index.as_ref().map(|index| index.rebuilds), Some(1));
+    }
+
+    #[test]
+    fn range_test() {
+        let sorted: SortedUnrolledList<_> = vec![5, 1, 9, 3, 3, 7, 3, 11].into_iter().collect();
+        let values = |slice: crate::slice::ListSlice<'_, i32>| slice.iter().copied().collect::<Vec<_>>();
+        assert_eq!(values(sorted.range(3..9)), vec![3, 3, 3, 5, 7]);
+        assert_eq!(values(sorted.range(3..=9)), vec![3, 3, 3, 5, 7, 9]);
+        assert_eq!(values(sorted.range(4..)), vec![5, 7, 9, 11]);
+        assert_eq!(values(sorted.range(..3)), vec![1]);
+        assert_eq!(values(sorted.range((Bound::Excluded(3), Bound::Included(7)))), vec![5, 7]);
+        assert_eq!(values(sorted.range(20..)), Vec::<i32>::new());
+        assert_eq!(values(sorted.range((Bound::Included(8), Bound::Excluded(4)))), Vec::<i32>::new());
+        assert_eq!(sorted.range(..).len(), 8);
+    }
+
+    #[test]
+    fn dedup_test() {
+        let mut set = SortedUnrolledList::with_capacity(3);
+        set.set_dedup(true);
+        let mut model = BTreeSet::new();
+        let mut seed = 9u32;
+        for _ in 0..400 {
+            seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+            let value = (seed >> 16) % 64;
+            if (seed >> 8) & 3 == 0 {
+                assert_eq!(set.remove(&value).is_some(), model.remove(&value));
+            } else {
+                assert_eq!(set.insert(value), model.insert(value));
+            }
+            check_invariants(set.as_list());
+        }
+        assert_eq!(set.iter().copied().collect::<Vec<_>>(), model.iter().copied().collect::<Vec<_>>());
+
+        let mut bag: SortedUnrolledList<_> = (0..100).map(|el| el / 4).collect();
+        assert_eq!(bag.len(), 100);
+        bag.set_dedup(true);
+        check_invariants(bag.as_list());
+        assert!(bag.is_dedup());
+        assert_eq!(bag.iter().copied().collect::<Vec<_>>(), (0..25).collect::<Vec<_>>());
+        bag.set_dedup(false);
+        assert!(bag.insert(3));
+        assert_eq!(bag.rank(&4), 5);
+    }
+}